@@ -0,0 +1,137 @@
+use serenity::utils::Colour;
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// Encodes a `size`x`size` PNG filled with a single colour.
+///
+/// The image data is written as uncompressed deflate blocks, a solid swatch is small enough that
+/// pulling in an image crate for real compression isn't worth it.
+pub fn color_swatch_png(c: Colour, size: u32) -> Vec<u8> {
+    let size = size.max(1);
+
+    let mut raw = Vec::with_capacity((size * (size * 3 + 1)) as usize);
+    for _ in 0..size {
+        // Each scanline is prefixed with its filter type, 0 being no filtering.
+        raw.push(0);
+        for _ in 0..size {
+            raw.extend_from_slice(&[c.r(), c.g(), c.b()]);
+        }
+    }
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&size.to_be_bytes());
+    ihdr.extend_from_slice(&size.to_be_bytes());
+    // 8 bit depth, truecolour, default compression/filter methods, no interlacing.
+    ihdr.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    let mut png = PNG_SIGNATURE.to_vec();
+    write_chunk(&mut png, b"IHDR", &ihdr);
+    write_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+/// Parses a `#rrggbb` (or `rrggbb`) hex color.
+pub fn parse_hex(text: &str) -> Option<Colour> {
+    let digits = text.trim().trim_start_matches('#');
+    // `from_str_radix` would also take a leading sign.
+    if digits.len() != 6 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    u32::from_str_radix(digits, 16).ok().map(Colour)
+}
+
+/// Discord's dark theme chat background.
+pub const DARK_THEME: Colour = Colour(0x36393F);
+/// Discord's light theme chat background.
+pub const LIGHT_THEME: Colour = Colour(0xFFFFFF);
+
+/// WCAG relative luminance, 0 for black up to 1 for white.
+pub fn relative_luminance(c: Colour) -> f64 {
+    fn linear(channel: u8) -> f64 {
+        let channel = channel as f64 / 255.0;
+        if channel <= 0.03928 {
+            channel / 12.92
+        } else {
+            ((channel + 0.055) / 1.055).powf(2.4)
+        }
+    }
+
+    0.2126 * linear(c.r()) + 0.7152 * linear(c.g()) + 0.0722 * linear(c.b())
+}
+
+/// WCAG contrast ratio between two colours, from 1 (identical luminance) up to 21 (black on white).
+pub fn contrast_ratio(a: Colour, b: Colour) -> f64 {
+    let (a, b) = (relative_luminance(a), relative_luminance(b));
+    let (lighter, darker) = if a > b { (a, b) } else { (b, a) };
+    (lighter + 0.05) / (darker + 0.05)
+}
+
+/// Nudges `c` towards white or black in 10% steps until its contrast with `other` reaches `target`,
+/// taking whichever direction gets there with the smallest change.
+///
+/// Returns `None` if even pure white/black doesn't get there.
+pub fn increase_contrast(c: Colour, other: Colour, target: f64) -> Option<Colour> {
+    let (r, g, b) = (c.r() as f64, c.g() as f64, c.b() as f64);
+
+    (1..=10).map(|step| step as f64 / 10.0).find_map(|t| {
+        [255.0, 0.0].iter().find_map(|goal| {
+            let mix = |channel: f64| (channel + (goal - channel) * t).round() as u8;
+            let candidate = Colour::from_rgb(mix(r), mix(g), mix(b));
+            Some(candidate).filter(|candidate| contrast_ratio(*candidate, other) >= target)
+        })
+    })
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+
+    let mut blocks = data.chunks(u16::MAX as usize).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (!(crc & 1)).wrapping_add(1);
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
@@ -0,0 +1,100 @@
+use std::{env, fs};
+
+use serde::Deserialize;
+
+/// Bot wide settings, read from the file at `CONFIG_FILE` with environment variables taking precedence.
+///
+/// The token is deliberately not part of this, it only ever comes from `DISCORD_TOKEN`.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BotConfig {
+    pub application_id: Option<u64>,
+    pub owner_id: Option<u64>,
+    pub host_role_id: Option<u64>,
+    // On unless turned off, mimicking was always on before it could be.
+    pub mimic_enabled: bool,
+    pub members_intent: bool,
+    pub force_command_sync: bool,
+    pub leaderboard_file: Option<String>,
+    pub teams_file: Option<String>,
+    pub questions_file: Option<String>,
+}
+
+impl Default for BotConfig {
+    fn default() -> BotConfig {
+        BotConfig {
+            application_id: None,
+            owner_id: None,
+            host_role_id: None,
+            mimic_enabled: true,
+            members_intent: false,
+            force_command_sync: false,
+            leaderboard_file: None,
+            teams_file: None,
+            questions_file: None,
+        }
+    }
+}
+
+impl BotConfig {
+    pub fn load() -> Result<BotConfig, String> {
+        let mut config = match env::var("CONFIG_FILE") {
+            Ok(path) => {
+                let contents = fs::read_to_string(&path).map_err(|err| format!("Cannot read config file {}: {}", path, err))?;
+                toml::from_str(&contents).map_err(|err| format!("Invalid config file {}: {}", path, err))?
+            },
+            Err(_) => BotConfig::default(),
+        };
+
+        if let Ok(id) = env::var("APPLICATION_ID") {
+            config.application_id = Some(parse_id("APPLICATION_ID", &id)?);
+        }
+
+        if let Ok(id) = env::var("OWNER_ID") {
+            config.owner_id = Some(parse_id("OWNER_ID", &id)?);
+        }
+
+        if let Ok(id) = env::var("HOST_ROLE_ID") {
+            config.host_role_id = Some(parse_id("HOST_ROLE_ID", &id)?);
+        }
+
+        if let Ok(value) = env::var("MIMIC_ENABLED") {
+            config.mimic_enabled = parse_flag(&value);
+        }
+
+        if let Ok(value) = env::var("MEMBERS_INTENT") {
+            config.members_intent = parse_flag(&value);
+        }
+
+        if let Ok(value) = env::var("FORCE_COMMAND_SYNC") {
+            config.force_command_sync = parse_flag(&value);
+        }
+
+        if let Ok(path) = env::var("LEADERBOARD_FILE") {
+            config.leaderboard_file = Some(path);
+        }
+
+        if let Ok(path) = env::var("TEAMS_FILE") {
+            config.teams_file = Some(path);
+        }
+
+        if let Ok(path) = env::var("QUESTIONS_FILE") {
+            config.questions_file = Some(path);
+        }
+
+        if config.application_id.is_none() {
+            return Err("Expected an application id, set APPLICATION_ID or application_id in the config file".to_string());
+        }
+
+        Ok(config)
+    }
+}
+
+fn parse_id(name: &str, value: &str) -> Result<u64, String> {
+    value.trim().parse().map_err(|_| format!("{} is not a valid id: {:?}", name, value))
+}
+
+fn parse_flag(value: &str) -> bool {
+    let value = value.trim();
+    value == "1" || value.eq_ignore_ascii_case("true")
+}
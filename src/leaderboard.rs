@@ -0,0 +1,67 @@
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use serenity::model::{guild::Role, id::GuildId};
+
+use crate::storage::{load_json, save_json_atomic};
+
+/// All-time game wins per team, kept on disk so it outlives a single game and restarts.
+///
+/// Wins are keyed by role id rather than team name so renaming a team keeps its record, the name is
+/// only remembered for display in case the role has been deleted since.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Leaderboard {
+    #[serde(skip)]
+    path: PathBuf,
+    guilds: HashMap<u64, HashMap<u64, TeamWins>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamWins {
+    pub name: String,
+    pub wins: u32,
+}
+
+impl Leaderboard {
+    /// Reads the leaderboard at `path`, starting an empty one if the file doesn't exist yet.
+    pub fn load(path: &Path) -> io::Result<Leaderboard> {
+        let mut leaderboard: Leaderboard = load_json(path)?.unwrap_or_default();
+        leaderboard.path = path.to_path_buf();
+        Ok(leaderboard)
+    }
+
+    fn save(&self) -> io::Result<()> {
+        save_json_atomic(&self.path, self)
+    }
+
+    /// Counts a win for the team with `role`, returning its new total.
+    ///
+    /// The win is kept in memory even if saving fails, so the error only means it may not survive a restart.
+    pub fn record_win(&mut self, guild: GuildId, role: &Role) -> io::Result<u32> {
+        let entry = self.guilds
+            .entry(guild.0)
+            .or_default()
+            .entry(role.id.0)
+            .or_insert_with(|| TeamWins { name: role.name.clone(), wins: 0 });
+        entry.name = role.name.clone();
+        entry.wins = entry.wins.saturating_add(1);
+
+        let wins = entry.wins;
+        self.save()?;
+        Ok(wins)
+    }
+
+    /// Teams with at least one win in `guild`, most wins first.
+    pub fn standings(&self, guild: GuildId) -> Vec<(u64, TeamWins)> {
+        let mut standings: Vec<(u64, TeamWins)> = self.guilds
+            .get(&guild.0)
+            .map(|teams| teams.iter().map(|(role_id, wins)| (*role_id, wins.clone())).collect())
+            .unwrap_or_default();
+        standings.sort_by(|(_, a), (_, b)| b.wins.cmp(&a.wins).then_with(|| a.name.cmp(&b.name)));
+        standings
+    }
+}
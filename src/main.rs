@@ -1,439 +1,5248 @@
+mod color;
+mod config;
+mod leaderboard;
+mod questions;
+mod role_queue;
+mod storage;
+mod welcomed;
+
 use std::{
+    borrow::Cow,
+    convert::TryFrom,
     env,
-    collections::HashMap,
+    fmt,
+    io,
+    path::{Path, PathBuf},
+    collections::{BTreeMap, HashMap, HashSet},
     sync::{Mutex, Arc},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    config::BotConfig,
+    leaderboard::Leaderboard,
+    questions::Questions,
+    role_queue::RoleEditQueue,
+    storage::{load_json, save_json_atomic},
+    welcomed::Welcomed,
 };
 
+use tracing::{debug, error, info, warn};
+use tracing_subscriber::EnvFilter;
+
 use serenity::{
     async_trait, 
-    client::bridge::gateway::GatewayIntents,
+    builder::{CreateAllowedMentions, CreateApplicationCommands, CreateEmbed},
+    client::bridge::gateway::{GatewayIntents, ShardManager},
     model::{
-        guild::{GuildStatus, Guild, Role},
+        channel::{ChannelType, GuildChannel, Reaction, ReactionType},
+        error::Error as ModelError,
+        permissions::Permissions,
+        guild::{Guild, Role},
         id::{
+            ApplicationId,
             ChannelId,
+            GuildId,
             RoleId,
+            UserId,
         },
         event::TypingStartEvent, 
         gateway::Ready,
+        user::User,
         interactions::{
             ApplicationCommand,
+            ApplicationCommandInteractionData,
+            ApplicationCommandInteractionDataOption,
             ApplicationCommandInteractionDataOptionValue,
+            ApplicationCommandOption,
+            ApplicationCommandOptionChoice,
             ApplicationCommandOptionType,
             Interaction,
+            InteractionApplicationCommandCallbackDataFlags,
             InteractionResponseType,
             InteractionType,
         },
     },
     utils::Colour,
     cache::Cache,
+    http::{
+        error::Error as HttpError,
+        AttachmentType,
+        GuildPagination,
+    },
     prelude::*,
 };
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+const MESSAGE_LIMIT: usize = 2000;
+const MOTTO_LIMIT: usize = 100;
+const GUILDS_PER_PAGE: usize = 20;
+// Interaction tokens stop working after 15 minutes, stop a little early rather than have edits start failing.
+const SCORE_WATCH_DURATION: Duration = Duration::from_secs(14 * 60);
+
+// WCAG's minimum for large text and graphics, role colors are mostly seen as names on the chat background.
+const BACKGROUND_CONTRAST: f64 = 3.0;
+// Below this two teams' colors are hard to tell apart, especially for colorblind players.
+const TEAM_CONTRAST: f64 = 1.5;
+
+const WELCOME: &'static str = "Thanks for adding me! To get a game going:
+1. Create a role named **Host** (or pick any role with `/host set`) and give it to whoever runs the game.
+2. Give each team a text channel and a role.
+3. Run `/team create channel:<channel> role:<role>` for each team, or `/team suggest` to see what's missing.
+Scores are tracked with `/team score adjust` and shown with `/team score list`.";
+
+// Guilds that haven't picked a host role with `/host set` use the role with this name.
+const DEFAULT_HOST_ROLE_NAME: &'static str = "Host";
+
+const NO_ACTIVE_GAME: &'static str = "No active game, run `/game start` first";
 
 const PERMISSION_DENIED: &'static str = "You do not have permission to use this command and it has been reported to the local authorities. Spend your last moments repenting.";
 
+struct ShardManagerContainer;
+
+impl TypeMapKey for ShardManagerContainer {
+    type Value = Arc<serenity::prelude::Mutex<ShardManager>>;
+}
+
 struct Handler {
     teams: Arc<Mutex<Teams>>,
-    host_role: Arc<Mutex<Option<RoleId>>>,
+    host_role: Arc<Mutex<HashMap<GuildId, RoleId>>>,
+    // Guilds whose host role was given by id, which turns off scanning them for a role named "Host".
+    host_role_fixed: Arc<Mutex<HashSet<GuildId>>>,
+    // Host role id from the bot config, used by whichever guild it belongs to.
+    configured_host_role: Option<RoleId>,
+    configs: Arc<Mutex<HashMap<GuildId, GuildConfig>>>,
+    // Where the configs and fixed host roles are saved whenever one changes.
+    guilds_path: PathBuf,
+    welcomed: Arc<Mutex<Welcomed>>,
+    // Guilds between `/game start` and `/game end`.
+    active_games: Arc<Mutex<HashSet<GuildId>>>,
+    role_edits: RoleEditQueue,
+    // Points each player's teams earned while they held the team role.
+    player_points: Arc<Mutex<HashMap<(GuildId, UserId), i64>>>,
+    leaderboard: Arc<Mutex<Leaderboard>>,
+    questions: Arc<Mutex<Questions>>,
+    // The question last asked in each channel until it's answered or runs out of time.
+    active_questions: Arc<Mutex<HashMap<ChannelId, ActiveQuestion>>>,
+    // The team role that buzzed in first on each channel's active question, and when.
+    buzzers: Arc<Mutex<HashMap<ChannelId, (RoleId, Instant)>>>,
+    // The most recent command in each guild, described for `/admin debug-interaction`.
+    last_interaction: Arc<Mutex<HashMap<GuildId, String>>>,
+    // Each host's private `/team score watch` panel.
+    score_watches: Arc<Mutex<HashMap<UserId, ScoreWatch>>>,
+    mimic: bool,
+    force_command_sync: bool,
+    owner: Option<UserId>,
+}
+
+// What a command responds with, mostly plain text.
+#[derive(Debug)]
+enum Response {
+    Text(String),
+    Scoreboard(ScoreboardEmbed),
+    TeamList(TeamListEmbed),
+    // Text followed by a preview of a team's new color.
+    Swatch(String, Colour),
+}
+
+impl From<String> for Response {
+    fn from(content: String) -> Response {
+        Response::Text(content)
+    }
+}
+
+#[derive(Debug)]
+enum CommandError {
+    MissingOption(&'static str),
+    NoHostRole,
+    PermissionDenied,
+    NoActiveGame,
+    CoopMode,
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::MissingOption(name) => write!(f, "Missing `{}`, your commands may be out of date, try again in a minute", name),
+            CommandError::NoHostRole => write!(f, "No host role found, create a role named Host or set one with `/host set`"),
+            CommandError::PermissionDenied => f.write_str(PERMISSION_DENIED),
+            CommandError::NoActiveGame => f.write_str(NO_ACTIVE_GAME),
+            CommandError::CoopMode => write!(f, "Scores are shared in cooperative mode, use `/score adjust` or switch modes with `/game config mode`"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct GuildConfig {
+    // Whether setup confirmations (team creation, renames, config changes) are only shown to the invoker.
+    ephemeral_admin: bool,
+    // Whether score adjustments must name the team explicitly instead of falling back to the current channel's team.
+    confirm_adjust: bool,
+    // Channel where hosts can react with ➕/➖ to a team member's message to adjust by a preset amount.
+    reaction_scoring: Option<(ChannelId, i64)>,
+    max_team_score: Option<i64>,
+    // Whether scoring commands are refused unless a game has been started with `/game start`.
+    require_active_game: bool,
+    mode: GameMode,
+    coop_target: Option<i64>,
+    // How `adjust-percent` rounds deltas that aren't a whole number of points.
+    percent_rounding: Rounding,
+    // Adjustments must be a multiple of the step, amounts that aren't are rejected or rounded to the nearest multiple.
+    score_step: Option<(i64, StepMode)>,
+    // Points a correct `/trivia answer` earns per question difficulty, overriding the default of 100 per level.
+    difficulty_points: BTreeMap<u8, i64>,
+}
+
+impl GuildConfig {
+    fn question_points(&self, difficulty: u8) -> i64 {
+        self.difficulty_points.get(&difficulty).copied().unwrap_or(difficulty as i64 * 100)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+enum StepMode {
+    Reject,
+    Round,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+enum GameMode {
+    // Teams compete against each other, the default.
+    Versus,
+    // The whole guild shares a single score.
+    Coop,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+enum Rounding {
+    // Halves round up, the default.
+    Nearest,
+    Down,
+    Up,
+}
+
+// What `/config export` hands out. Role and channel ids don't carry over to another guild, so their names are kept
+// alongside them for `/config import` to find the equivalent there.
+#[derive(Debug, Serialize, Deserialize)]
+struct ConfigExport {
+    config: GuildConfig,
+    host_role: Option<(RoleId, String)>,
+    reaction_channel_name: Option<String>,
+}
+
+impl Default for GuildConfig {
+    fn default() -> GuildConfig {
+        GuildConfig {
+            ephemeral_admin: true,
+            confirm_adjust: false,
+            reaction_scoring: None,
+            max_team_score: None,
+            require_active_game: false,
+            mode: GameMode::Versus,
+            coop_target: None,
+            percent_rounding: Rounding::Nearest,
+            score_step: None,
+            difficulty_points: BTreeMap::new(),
+        }
+    }
+}
+
+// Returns the score limited to the guild's bounds and whether it had to be limited.
+fn clamp_score(score: i64, config: &GuildConfig) -> (i64, bool) {
+    match config.max_team_score {
+        Some(max) if score > max => (max, true),
+        _ => (score, false),
+    }
+}
+
+// Adds `delta` to `score` within the guild's bounds, or `None` if it overflows, which is rejected rather than clamped
+// since the result would be meaningless.
+fn add_to_score(score: i64, delta: i64, config: &GuildConfig) -> Option<(i64, bool)> {
+    score.checked_add(delta).map(|score| clamp_score(score, config))
+}
+
+// Returns the adjustment fitted to the guild's score step and whether it had to be rounded, or the step if it was rejected.
+fn apply_score_step(amount: i64, config: &GuildConfig) -> Result<(i64, bool), i64> {
+    let (step, mode) = match config.score_step {
+        Some((step, mode)) if step > 1 => (step, mode),
+        _ => return Ok((amount, false)),
+    };
+
+    let remainder = amount.rem_euclid(step);
+    match mode {
+        _ if remainder == 0 => Ok((amount, false)),
+        StepMode::Reject => Err(step),
+        StepMode::Round => {
+            let down = amount - remainder;
+            let rounded = if remainder * 2 >= step { down.checked_add(step) } else { Some(down) };
+            rounded.map(|rounded| (rounded, true)).ok_or(step)
+        },
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CommandCategory {
+    Admin,
+    Game,
+    // Only ever shown to the invoker, regardless of the guild's config.
+    Private,
 }
 
 struct Teams {
     teams: HashMap<ChannelId, Team>,
+    // Teams set aside between sessions, kept with their score but left out of scoreboards and scoring.
+    archived: HashMap<ChannelId, Team>,
+    // Host chosen scoreboard order per guild, for dramatic reveals.
+    manual_order: HashMap<GuildId, Vec<ChannelId>>,
+    // Bumped on every mutation so saves and score watches know when they're stale.
+    version: u64,
+    // Scoreboards rendered since the last mutation, so guilds and layouts don't evict each other.
+    rendered: HashMap<(GuildId, ScoreboardOptions), String>,
+    // Same for the embed scoreboards, by the team they highlight.
+    rendered_embeds: HashMap<(GuildId, Option<ChannelId>), ScoreboardEmbed>,
+    audits: HashMap<GuildId, ScoreAudit>,
+    // The score each guild playing cooperatively has earned together.
+    coop_scores: HashMap<GuildId, i64>,
+    // Teams loaded from disk whose guild hasn't been cached yet, so their roles are still unknown.
+    pending: HashMap<ChannelId, SavedTeam>,
+    // Where teams are saved, and the version that was last written there.
+    path: PathBuf,
+    saved_version: u64,
+}
+
+// What's kept of a team on disk. Roles can't round trip through JSON, so only the id is stored and the role is
+// looked up again once its guild shows up in the cache.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SavedTeam {
+    channel_id: ChannelId,
+    guild_id: GuildId,
+    role_id: RoleId,
+    score: i64,
+    icon: Option<String>,
+    division: Option<String>,
+    motto: Option<String>,
+    archived: bool,
+}
+
+// The teams file. Files written before shared scores were saved only hold the list of teams.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum SavedTeams {
+    Teams(Vec<SavedTeam>),
+    WithCoop {
+        teams: Vec<SavedTeam>,
+        coop_scores: HashMap<u64, i64>,
+    },
+}
+
+// Guild settings kept on disk: each guild's config and the host role picked with `/host set`. Host roles found by
+// their name aren't saved, they're looked up again whenever the guild is cached.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct SavedGuilds {
+    #[serde(skip)]
+    path: PathBuf,
+    configs: HashMap<u64, GuildConfig>,
+    host_roles: HashMap<u64, RoleId>,
+}
+
+impl SavedGuilds {
+    // Reads the guild settings at `path`, starting with the defaults if the file doesn't exist yet.
+    fn load(path: &Path) -> io::Result<SavedGuilds> {
+        let mut saved: SavedGuilds = load_json(path)?.unwrap_or_default();
+        saved.path = path.to_path_buf();
+        Ok(saved)
+    }
+}
+
+// Double-entry check on scoring: every delta a scoring command applies is also added up here, so the scores can
+// be reconciled against it. Changes that aren't scoring (archiving a team, setting all scores) move the baseline.
+#[derive(Debug, Default, Clone, Copy)]
+struct ScoreAudit {
+    baseline: i128,
+    deltas: i128,
+}
+
+// Scores have moved by `actual` since the audit started, but scoring only accounts for `expected` of that.
+#[derive(Debug, Clone, Copy)]
+struct Discrepancy {
+    expected: i128,
+    actual: i128,
+}
+
+// Why a team couldn't be created.
+#[derive(Debug, Clone, Copy)]
+enum CreateTeamError {
+    // The channel already has a team.
+    ChannelTaken,
+    // The role already backs an active team of the same guild, in this channel. Archived teams aren't checked.
+    RoleTaken(ChannelId),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+struct ScoreboardOptions {
+    by_division: bool,
+    // One plain line per team for narrow (mobile) screens.
+    compact: bool,
+    // Machine readable output for external tooling.
+    json: bool,
+    // Team of the member viewing the scoreboard, so they can find themselves.
+    highlight: Option<ChannelId>,
+}
+
+// The default scoreboard, ranked, taken from the teams so it can be sent as an embed once their lock is released.
+#[derive(Debug, Clone)]
+struct ScoreboardEmbed {
+    rows: Vec<ScoreboardRow>,
+    total: i64,
+}
+
+#[derive(Debug, Clone)]
+struct ScoreboardRow {
+    // Position by score, which differs from the row's position when a manual order is set.
+    rank: usize,
+    name: String,
+    role_id: RoleId,
+    colour: Colour,
+    score: i64,
+    bonus: i64,
+    highlighted: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ActiveQuestion {
+    // Index into the question bank.
+    index: usize,
+    // When a timed question's answer is revealed, if nobody answers first.
+    deadline: Option<Instant>,
+}
+
+impl ActiveQuestion {
+    fn expired(&self) -> bool {
+        self.deadline.map_or(false, |deadline| Instant::now() >= deadline)
+    }
+}
+
+// One page of `/team list`, alphabetical by team name.
+#[derive(Debug, Clone)]
+struct TeamListEmbed {
+    rows: Vec<TeamListRow>,
+    page: usize,
+    pages: usize,
+    total: usize,
+}
+
+#[derive(Debug, Clone)]
+struct TeamListRow {
+    name: String,
+    channel_id: ChannelId,
+    colour: Colour,
+    score: i64,
+}
+
+// An ephemeral scoreboard that's edited through its interaction token whenever the scores change.
+#[derive(Debug, Clone)]
+struct ScoreWatch {
+    guild_id: GuildId,
+    application_id: ApplicationId,
+    token: String,
+    options: ScoreboardOptions,
+    // Teams version the panel last showed.
+    version: u64,
+    expires: Instant,
 }
 
 #[derive(Debug, Clone)]
 struct Team {
     role: Role,
     score: i64,
+    icon: Option<String>,
+    division: Option<String>,
+    previous_colour: Option<Colour>,
+    // Points on top of `score` until the deadline, after which they drop off on their own.
+    temp_bonus: Option<(i64, Instant)>,
+    motto: Option<String>,
+}
+
+impl Team {
+    fn bonus(&self) -> i64 {
+        match self.temp_bonus {
+            Some((bonus, expires)) if Instant::now() < expires => bonus,
+            _ => 0,
+        }
+    }
+
+    // The score as shown to players, including any bonus that hasn't expired yet.
+    fn total_score(&self) -> i64 {
+        self.score.saturating_add(self.bonus())
+    }
+
+    fn display_name(&self) -> String {
+        match &self.icon {
+            Some(icon) => format!("{} {}", icon, self.role.name),
+            None => self.role.name.clone(),
+        }
+    }
+}
+
+// Accepts custom emoji mentions (`<:name:id>`/`<a:name:id>`) or a short unicode emoji, made of characters from the
+// emoji blocks and the joiners, variation selectors and tags that combine them.
+fn is_valid_emoji(emoji: &str) -> bool {
+    if emoji.starts_with('<') && emoji.ends_with('>') {
+        let inner = &emoji[1..emoji.len() - 1];
+        let parts: Vec<&str> = inner.split(':').collect();
+        return parts.len() == 3
+            && (parts[0] == "" || parts[0] == "a")
+            && !parts[1].is_empty()
+            && !parts[2].is_empty()
+            && parts[2].chars().all(|c| c.is_ascii_digit());
+    }
+
+    let is_pictograph = |c: char| matches!(c as u32,
+        0x00A9 | 0x00AE | 0x203C | 0x2049 | 0x2122 | 0x2139 | 0x24C2 | 0x3030 | 0x303D | 0x3297 | 0x3299
+        | 0x2190..=0x21FF | 0x2300..=0x23FF | 0x25A0..=0x27BF | 0x2900..=0x297F | 0x2B00..=0x2BFF | 0x1F000..=0x1FAFF
+    );
+    let is_combining = |c: char| matches!(c as u32, 0x200D | 0xFE0E | 0xFE0F | 0x20E3 | 0xE0020..=0xE007F);
+    // Keycap emoji such as #️⃣ start with an ascii character, which is only allowed in front of the keycap.
+    let is_keycap_base = |c: char| c == '#' || c == '*' || c.is_ascii_digit();
+    let keycap = emoji.starts_with(is_keycap_base) && emoji.ends_with('\u{20E3}');
+
+    let count = emoji.chars().count();
+    count > 0
+        && count <= 16
+        && (keycap || emoji.chars().any(is_pictograph))
+        && emoji.chars().enumerate().all(|(i, c)| is_pictograph(c) || is_combining(c) || (keycap && i == 0 && is_keycap_base(c)))
 }
 
 impl Teams {
     fn new() -> Teams {
         Teams {
             teams: HashMap::new(),
+            archived: HashMap::new(),
+            manual_order: HashMap::new(),
+            version: 0,
+            rendered: HashMap::new(),
+            rendered_embeds: HashMap::new(),
+            audits: HashMap::new(),
+            coop_scores: HashMap::new(),
+            pending: HashMap::new(),
+            path: PathBuf::new(),
+            saved_version: 0,
         }
     }
 
-    fn create_team(&mut self, channel: ChannelId, role: Role) {
-        self.teams.entry(channel).or_insert(Team {
-            role: role,
-            score: 0,
-        });
+    // Reads the teams saved at `path`, starting without any if the file doesn't exist yet.
+    fn load_from_path(path: &Path) -> io::Result<Teams> {
+        let (saved, coop_scores) = match load_json(path)? {
+            Some(SavedTeams::Teams(saved)) => (saved, HashMap::new()),
+            Some(SavedTeams::WithCoop { teams, coop_scores }) => (teams, coop_scores),
+            None => (Vec::new(), HashMap::new()),
+        };
+
+        let mut teams = Teams::new();
+        teams.path = path.to_path_buf();
+        teams.pending = saved.into_iter().map(|team| (team.channel_id, team)).collect();
+        teams.coop_scores = coop_scores.into_iter().map(|(guild_id, score)| (GuildId(guild_id), score)).collect();
+        Ok(teams)
     }
 
-    fn get_team(&mut self, channel: &ChannelId) -> Option<Team> {
-        self.teams.get(channel).cloned()
+    fn save_to_path(&self, path: &Path) -> io::Result<()> {
+        let saved_team = |channel_id: &ChannelId, team: &Team, archived: bool| SavedTeam {
+            channel_id: *channel_id,
+            guild_id: team.role.guild_id,
+            role_id: team.role.id,
+            score: team.score,
+            icon: team.icon.clone(),
+            division: team.division.clone(),
+            motto: team.motto.clone(),
+            archived: archived,
+        };
+
+        // Teams that haven't been resolved yet are written back as they were loaded, so they aren't lost.
+        let saved: Vec<SavedTeam> = self.teams
+            .iter()
+            .map(|(channel_id, team)| saved_team(channel_id, team, false))
+            .chain(self.archived.iter().map(|(channel_id, team)| saved_team(channel_id, team, true)))
+            .chain(self.pending.values().cloned())
+            .collect();
+        let coop_scores = self.coop_scores.iter().map(|(guild_id, score)| (guild_id.0, *score)).collect();
+
+        save_json_atomic(path, &SavedTeams::WithCoop { teams: saved, coop_scores: coop_scores })
     }
-}
 
-impl Handler {
-    fn new() -> Handler {
-        Handler {
-            teams: Arc::new(Mutex::new(Teams::new())),
-            host_role: Arc::new(Mutex::new(None)),
+    // Saves the teams if anything changed since they were last written.
+    fn save(&mut self) -> io::Result<()> {
+        if self.version == self.saved_version {
+            return Ok(())
         }
+
+        self.save_to_path(&self.path)?;
+        self.saved_version = self.version;
+        Ok(())
     }
 
-    fn create_team(&self, channel: ChannelId, role: Role) {
-        let mut teams_data = self.teams.lock().unwrap();
-        teams_data.create_team(channel, role)
+    // Restores the loaded teams of `guild_id` now that its roles are known. Teams whose role has been deleted
+    // since are dropped.
+    fn resolve_pending(&mut self, guild_id: GuildId, roles: &HashMap<RoleId, Role>) {
+        let channels: Vec<ChannelId> = self.pending
+            .values()
+            .filter(|team| team.guild_id == guild_id)
+            .map(|team| team.channel_id)
+            .collect();
+        if channels.is_empty() {
+            return
+        }
+
+        self.touch();
+        for channel_id in channels {
+            let saved = match self.pending.remove(&channel_id) {
+                Some(saved) => saved,
+                None => continue,
+            };
+            let role = match roles.get(&saved.role_id) {
+                Some(role) => role.clone(),
+                None => {
+                    warn!(%guild_id, %channel_id, role_id = %saved.role_id, "Dropping saved team, its role no longer exists");
+                    continue
+                },
+            };
+            // A host may have recreated the team before its guild came back.
+            if self.teams.contains_key(&channel_id) || self.team_exists_for_role(guild_id, role.id).is_some() {
+                info!(%guild_id, %channel_id, "Dropping saved team, it has been created again since");
+                continue
+            }
+
+            let team = Team {
+                role: role,
+                score: saved.score,
+                icon: saved.icon,
+                division: saved.division,
+                previous_colour: None,
+                temp_bonus: None,
+                motto: saved.motto,
+            };
+            if saved.archived {
+                self.archived.insert(channel_id, team);
+            } else {
+                // Restoring isn't scoring, the audit starts from the saved scores.
+                self.shift_baseline(guild_id, saved.score as i128);
+                self.teams.insert(channel_id, team);
+            }
+        }
     }
-}
 
-#[async_trait]
-impl EventHandler for Handler {
-    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
-        if interaction.kind == InteractionType::ApplicationCommand {
-            if let Some(data) = interaction.data.as_ref() {
-                let content = match data.name.as_str() {
-                    "ping" => "pong".to_string(),
-                    "id" => {
-                        let options = data
-                            .options
-                            .get(0)
-                            .expect("Expected user option")
-                            .resolved
-                            .as_ref()
-                            .expect("Expected user object");
+    fn score_sum(&self, guild_id: GuildId) -> i128 {
+        self.teams
+            .values()
+            .filter(|team| team.role.guild_id == guild_id)
+            .map(|team| team.score as i128)
+            .sum()
+    }
 
-                        if let ApplicationCommandInteractionDataOptionValue::User(user, _member) =
-                            options
-                        {
-                            format!("{}'s id is {}", user.tag(), user.id)
-                        } else {
-                            "Please provide a valid user".to_string()
-                        }
-                    },
-                    "team" => {
-                        let suboption = data.options.get(0).expect("Expected sub option");
-                        match suboption.name.as_str().clone() {
-                            "rename" => {
-                                let name_arg = suboption
-                                    .options
-                                    .get(0)
-                                    .expect("Expected new team name")
-                                    .resolved
-                                    .as_ref()
-                                    .expect("Expected string");
-
-                                match (name_arg, interaction.channel_id) {
-                                    (ApplicationCommandInteractionDataOptionValue::String(new_name), Some(channel_id)) => {
-                                        {
-                                            let teams = self.teams.lock().unwrap().get_team(&channel_id);
-                                            match teams {
-                                                Some(team) => {
-                                                    match team.role.edit(ctx.http.clone(), |r| {
-                                                        r.name(new_name);
-                                                        r
-                                                    }).await {
-                                                            Ok(role) => format!("Team name is now {}", new_name),
-                                                            Err(err) => format!("Failed to rename team: {:?}", err),
-                                                        }
-                                                },
-                                                _ => "Failed to rename team, could not find team".to_string(),
-                                            }
-                                        }
-                                    },
-                                    _ => "Failed to rename team, invalid argument or channel id".to_string()
-                                }
-                            },
-                            "recolor" => {
-                                let mut components = Vec::new();
-                                for component in &suboption.options {
-                                    if let ApplicationCommandInteractionDataOptionValue::Integer(component) = component.resolved.as_ref().expect("Expected integer") {
-                                        components.push(component);
-                                    }
-                                }
+    fn record_delta(&mut self, guild_id: GuildId, delta: i64) {
+        self.audits.entry(guild_id).or_default().deltas += delta as i128;
+    }
 
-                                let new_color = Colour::from_rgb(
-                                    *components[0] as u8, 
-                                    *components[1] as u8, 
-                                    *components[2] as u8
-                                );
-
-                                match interaction.channel_id {
-                                    Some(channel_id) => {
-                                        {
-                                            let teams = self.teams.lock().unwrap().get_team(&channel_id);
-                                            match teams {
-                                                Some(team) => {
-                                                    match team.role.edit(ctx.http.clone(), |r| {
-                                                        r.colour(new_color.0 as u64);
-                                                        r
-                                                    }).await {
-                                                        Ok(role) => format!("Team color is now ({}, {}, {})", new_color.r(), new_color.g(), new_color.b()),
-                                                        Err(err) => format!("Failed to rename team: {:?}", err),
-                                                    }
-                                                },
-                                                _ => "Failed to rename team, could not find team".to_string(),
-                                            }
-                                        }
-                                    },
-                                    _ => "Failed to rename team, invalid argument or channel id".to_string()
-                                }
-                            },
-                            "create" => {
-                                let host_role = self.host_role.lock().unwrap().unwrap();
-
-                                match &interaction.member {
-                                    Some(member) => {
-                                        match member.user
-                                            .has_role(&ctx.http, interaction.guild_id.expect("Expected guild id"), host_role).await.expect("Expected bool") {
-                                            true => {
-                                                let channel_arg = suboption.options.get(0).expect("Expected channel id").resolved.as_ref().expect("Expected Channel");
-                                                let role_arg = suboption.options.get(1).expect("Expected role id").resolved.as_ref().expect("Expected Role");
-
-                                                match (channel_arg, role_arg) {
-                                                    (ApplicationCommandInteractionDataOptionValue::Channel(partial_channel),
-                                                    ApplicationCommandInteractionDataOptionValue::Role(role)) => {
-                                                        self.create_team(partial_channel.id, role.clone());
-                                                        "Created new team".to_string()
-                                                    },
-                                                    _ => "Failed to create team, unknown channel or role".to_string(),
-                                                }
-                                            },
-                                            false => PERMISSION_DENIED.to_string(),
-                                        }
-                                    },
-                                    None => "No member for interaction".to_string(),
-                                }
-                            },
-                            "score" => {
-                                let score_options = suboption.options.get(0).expect("Expected sub-sub option");
-                                match score_options.name.as_str().clone() {
-                                    "list" => {
-                                        let teams = &self.teams.lock().unwrap();
-                                        let mut score_list = Vec::new();
-                                        for (id, team) in &teams.teams {
-                                            score_list.push(format!("{}: {}", team.role.name, team.score));
-                                        }
+    fn shift_baseline(&mut self, guild_id: GuildId, amount: i128) {
+        self.audits.entry(guild_id).or_default().baseline += amount;
+    }
 
-                                        if score_list.len() == 0 {
-                                            "No teams created".to_string()
-                                        } else {
-                                            score_list.join(", ")
-                                        }
-                                    },
-                                    "adjust" => {
-                                        let host_role = self.host_role.lock().unwrap().unwrap();
-                                        match &interaction.member {
-                                            Some(member) => {
-                                                match member.user
-                                                    .has_role(&ctx.http, interaction.guild_id.expect("Expected guild id"), host_role).await.expect("Expected bool") {
-                                                    true => {
-                                                        match (*self.teams.lock().unwrap()).teams.get_mut(&interaction.channel_id.expect("Expected channel id")) {
-                                                            Some(team) => {
-                                                                let adjust_arg = score_options
-                                                                    .options
-                                                                    .get(0)
-                                                                    .expect("Expected adjustment amount")
-                                                                    .clone()
-                                                                    .resolved
-                                                                    .expect("Expected integer");
-
-                                                                match adjust_arg {
-                                                                    ApplicationCommandInteractionDataOptionValue::Integer(adjust) => {
-                                                                        team.score += adjust;
-                                                                        format!("Team score adjusted by {}, score is now {} in total", adjust, team.score)
-                                                                    }
-                                                                    _ => "Adjustment wrong type, could not adjust".to_string(),
-                                                                }
-                                                            },
-                                                            None => "Missing team, could not adjust".to_string(),
-                                                        }
-                                                    },
-                                                    false => PERMISSION_DENIED.to_string(),
-                                                }
-                                            },
-                                            None => "No member for interaction".to_string(),
-                                        }
-                                    }
-                                    _ => {
-                                        "Invalid team->score suboption".to_string()
-                                    }
-                                }
-                            },
-                            _ => "Invalid team suboption".to_string(),
-                        }
-                    },
-                    _ => "Invalid command".to_string(),
-                };
+    fn reconcile(&self, guild_id: GuildId) -> Result<(), Discrepancy> {
+        let audit = self.audits.get(&guild_id).copied().unwrap_or_default();
+        let actual = self.score_sum(guild_id) - audit.baseline;
+        if actual == audit.deltas {
+            Ok(())
+        } else {
+            Err(Discrepancy { expected: audit.deltas, actual: actual })
+        }
+    }
 
-                if let Err(why) = interaction
-                    .create_interaction_response(&ctx.http, |response| {
-                        response
-                            .kind(InteractionResponseType::ChannelMessageWithSource)
-                            .interaction_response_data(|message| message.content(content))
-                    })
-                    .await
-                {
-                    println!("Cannot respond to slash command: {}", why);
-                }
-            }
+    fn touch(&mut self) {
+        self.version += 1;
+        self.rendered.clear();
+        self.rendered_embeds.clear();
+    }
+
+    // The shared score of `guild_id`, 0 until it has scored in a cooperative game.
+    fn coop_score(&self, guild_id: GuildId) -> i64 {
+        self.coop_scores.get(&guild_id).cloned().unwrap_or(0)
+    }
+
+    // Adds `delta` to the shared score of `guild_id` within the guild's bounds, returning the new score and whether
+    // it was capped, or `None` if it's out of range.
+    fn adjust_coop_score(&mut self, guild_id: GuildId, delta: i64, config: &GuildConfig) -> Option<(i64, bool)> {
+        let (score, clamped) = add_to_score(self.coop_score(guild_id), delta, config)?;
+        self.touch();
+        self.coop_scores.insert(guild_id, score);
+        Some((score, clamped))
+    }
+
+    fn archive(&mut self, channel: &ChannelId) -> Option<&Team> {
+        let team = self.teams.remove(channel)?;
+        self.touch();
+        self.shift_baseline(team.role.guild_id, -(team.score as i128));
+        Some(self.archived.entry(*channel).or_insert(team))
+    }
+
+    // Fails with the archived team's name if its channel or role has been given to another team since.
+    fn unarchive(&mut self, channel: &ChannelId) -> Option<Result<&Team, String>> {
+        let archived = self.archived.get(channel)?;
+        if self.teams.contains_key(channel) || self.team_exists_for_role(archived.role.guild_id, archived.role.id).is_some() {
+            return Some(Err(archived.role.name.clone()));
         }
+
+        let team = self.archived.remove(channel)?;
+        self.touch();
+        self.shift_baseline(team.role.guild_id, team.score as i128);
+        Some(Ok(self.teams.entry(*channel).or_insert(team)))
     }
 
-    async fn typing_start(&self, ctx: Context, start: TypingStartEvent) {
-        {
-            let cache: &Cache = ctx.as_ref();
-            if start.user_id == cache.current_user_id().await {
-                return
+    fn create_team(&mut self, channel: ChannelId, role: Role) -> Result<(), CreateTeamError> {
+        if self.teams.contains_key(&channel) {
+            return Err(CreateTeamError::ChannelTaken)
+        }
+        if let Some(existing) = self.team_exists_for_role(role.guild_id, role.id) {
+            return Err(CreateTeamError::RoleTaken(existing))
+        }
+
+        self.touch();
+        self.teams.insert(channel, Team {
+            role: role,
+            score: 0,
+            icon: None,
+            division: None,
+            previous_colour: None,
+            temp_bonus: None,
+            motto: None,
+        });
+        Ok(())
+    }
+
+    // Drops expired bonuses, so cached scoreboards stop showing them.
+    fn expire_bonuses(&mut self) {
+        let now = Instant::now();
+        let mut expired = false;
+        for team in self.teams.values_mut() {
+            if let Some((_, expires)) = team.temp_bonus {
+                if now >= expires {
+                    team.temp_bonus = None;
+                    expired = true;
+                }
             }
         }
 
-        if let Ok(user) = start.user_id.to_user(ctx.clone()).await {
-            println!("mimic: {:?}", user.name);
-            start.channel_id.broadcast_typing(ctx).await;
+        if expired {
+            self.touch();
         }
     }
 
-    async fn ready(&self, ctx: Context, ready: Ready) {
-        println!("{} is connected!", ready.user.name);
+    fn get_team(&mut self, channel: &ChannelId) -> Option<Team> {
+        self.teams.get(channel).cloned()
+    }
 
-        for guild in ready.guilds {
-            let commands = guild.id().set_application_commands(&ctx.http, |commands| {
-                commands
-                    .create_application_command(|command| {
-                        command.name("ping").description("A ping command")
-                    })
-                    .create_application_command(|command| {
-                        command.name("id").description("Get a user id").create_option(|option| {
-                            option
-                                .name("id")
-                                .description("The user to lookup")
-                                .kind(ApplicationCommandOptionType::User)
-                                .required(true)
-                        })
-                    })
-                    .create_application_command(|command| {
-                        command
-                            .name("team")
-                            .description("Team options")
-                            .create_option(|option| {
-                                option
-                                    .name("rename")
-                                    .description("Rename team.")
-                                    .kind(ApplicationCommandOptionType::SubCommand)
-                                    .create_sub_option(|option| {
-                                        option
-                                            .name("name")
-                                            .description("New team name")
-                                            .kind(ApplicationCommandOptionType::String)
-                                            .required(true)
-                                    })
-                            })
-                            .create_option(|option| {
-                                option
-                                    .name("recolor")
-                                    .description("Recolor team")
-                                    .kind(ApplicationCommandOptionType::SubCommand)
-                                    .create_sub_option(|option| {
-                                        option
-                                            .name("red")
-                                            .description("Red")
-                                            .kind(ApplicationCommandOptionType::Integer)
-                                            .required(true)
-                                    })
-                                    .create_sub_option(|option| {
-                                        option
-                                            .name("green")
-                                            .description("Green")
-                                            .kind(ApplicationCommandOptionType::Integer)
-                                            .required(true)
-                                    })
-                                    .create_sub_option(|option| {
-                                        option
-                                            .name("blue")
-                                            .description("Blue")
-                                            .kind(ApplicationCommandOptionType::Integer)
-                                            .required(true)
-                                    })
-                            })
-                            .create_option(|option| {
-                                option
-                                    .name("create")
-                                    .description("Create a team out of an existing channel/role pair.")
-                                    .kind(ApplicationCommandOptionType::SubCommand)
-                                    .create_sub_option(|option| {
-                                        option
-                                            .name("channel")
-                                            .description("Channel to use for team.")
-                                            .kind(ApplicationCommandOptionType::Channel)
-                                            .required(true)
-                                    })
-                                    .create_sub_option(|option| {
-                                        option
-                                            .name("role")
-                                            .description("Role to use for team.")
-                                            .kind(ApplicationCommandOptionType::Role)
-                                            .required(true)
-                                    })
-                            })
-                            .create_option(|option| {
-                                option
-                                    .name("score")
-                                    .description("Team scores.")
-                                    .kind(ApplicationCommandOptionType::SubCommandGroup)
-                                    .create_sub_option(|option| {
-                                        option
-                                            .name("list")
-                                            .description("View current score of teams")
-                                            .kind(ApplicationCommandOptionType::SubCommand)
-                                    })
-                                    .create_sub_option(|option| {
-                                        option
-                                            .name("adjust")
-                                            .description("Adjust score for team")
-                                            .kind(ApplicationCommandOptionType::SubCommand)
-                                            .create_sub_option(|option| {
-                                                option
-                                                    .name("amount")
-                                                    .description("Amount to adjust score")
-                                                    .kind(ApplicationCommandOptionType::Integer)
-                                                    .required(true)
-                                        })
-                                    })
-                            })
-                    })
-            })
-            .await;
+    fn set_all(&mut self, guild_id: GuildId, value: i64) -> usize {
+        self.touch();
+        let before = self.score_sum(guild_id);
+        let mut count = 0;
+        for team in self.teams.values_mut().filter(|team| team.role.guild_id == guild_id) {
+            team.score = value;
+            count += 1;
+        }
 
-            let fetched_guild = ctx.cache.guild(guild.id()).await;
-            if let Some(guild) = fetched_guild {
-                    for (role_id, role) in guild.roles {
-                        if role.name == "Host" {
-                            let mut host_role = self.host_role.lock().unwrap();
-                            *host_role = Some(role.id.clone());
-                        }
-                    }
-            }
+        let after = self.score_sum(guild_id);
+        self.shift_baseline(guild_id, after - before);
+        count
+    }
 
-            println!("I have the following global slash command(s): {:?}", commands);
+    // Zeroes every team in the guild for a new round, dropping their bonuses too so nothing carries over.
+    fn reset_all(&mut self, guild_id: GuildId) -> usize {
+        self.touch();
+        let before = self.score_sum(guild_id);
+        let mut count = 0;
+        for team in self.teams.values_mut().filter(|team| team.role.guild_id == guild_id) {
+            team.score = 0;
+            team.temp_bonus = None;
+            count += 1;
         }
 
+        let after = self.score_sum(guild_id);
+        self.shift_baseline(guild_id, after - before);
+        count
     }
-}
 
-#[tokio::main]
-async fn main() {
-    // Configure the client with your Discord bot token in the environment.
-    let token = env::var("DISCORD_TOKEN").expect("Expected a token in the environment");
+    // Exchanges the base scores of two teams, returning both teams' names and new scores. Temporary bonuses stay put.
+    fn swap_scores(&mut self, a: &ChannelId, b: &ChannelId) -> Option<((String, i64), (String, i64))> {
+        if a == b || !self.teams.contains_key(a) || !self.teams.contains_key(b) {
+            return None
+        }
 
-    // The Application Id is usually the Bot User Id.
-    let application_id: u64 =
-        env::var("APPLICATION_ID").expect("Expected an application id in the environment").parse().expect("application id is not a valid id");
+        self.touch();
+        let a_score = self.teams[a].score;
+        let b_score = self.teams[b].score;
 
-    // Build our client.
-    let mut client = Client::builder(token)
-        .event_handler(Handler::new())
-        .application_id(application_id)
-        .await
-        .expect("Error creating client");
+        let team_a = self.teams.get_mut(a)?;
+        team_a.score = b_score;
+        let a_summary = (team_a.display_name(), team_a.total_score());
 
-    // Finally, start a single shard, and start listening to events.
-    //
-    // Shards will automatically attempt to reconnect, and will perform
-    // exponential backoff until it reconnects.
-    if let Err(why) = client.start().await {
-        println!("Client error: {:?}", why);
+        let team_b = self.teams.get_mut(b)?;
+        team_b.score = a_score;
+        let b_summary = (team_b.display_name(), team_b.total_score());
+
+        Some((a_summary, b_summary))
+    }
+
+    // Creating a team is retried by hosts when responses time out, so a role only ever backs one team.
+    fn team_exists_for_role(&self, guild_id: GuildId, role_id: RoleId) -> Option<ChannelId> {
+        self.teams
+            .iter()
+            .find(|(_, team)| team.role.guild_id == guild_id && team.role.id == role_id)
+            .map(|(channel_id, _)| *channel_id)
     }
-}
\ No newline at end of file
+
+    // Resolves a channel mention, channel id or team name to the team's channel.
+    fn resolve_team_ref(&self, guild_id: GuildId, reference: &str) -> Option<ChannelId> {
+        let reference = reference.trim();
+        let id = reference.trim_start_matches("<#").trim_end_matches('>');
+        if let Ok(id) = id.parse::<u64>() {
+            let channel_id = ChannelId(id);
+            return self.teams.get(&channel_id).filter(|team| team.role.guild_id == guild_id).map(|_| channel_id);
+        }
+
+        let name = normalize_team_name(reference);
+        self.teams
+            .iter()
+            .find(|(_, team)| team.role.guild_id == guild_id && normalize_team_name(&team.role.name) == name)
+            .map(|(channel_id, _)| *channel_id)
+    }
+
+    fn team_for_roles(&self, roles: &[RoleId]) -> Option<ChannelId> {
+        self.teams
+            .iter()
+            .find(|(_, team)| roles.contains(&team.role.id))
+            .map(|(channel_id, _)| *channel_id)
+    }
+
+    fn scoreboard(&mut self, guild_id: GuildId, options: ScoreboardOptions) -> String {
+        self.expire_bonuses();
+        if let Some(rendered) = self.rendered.get(&(guild_id, options)) {
+            return rendered.clone();
+        }
+
+        let rendered = self.render_scoreboard(guild_id, options);
+        self.rendered.insert((guild_id, options), rendered.clone());
+        rendered
+    }
+
+    // Highest score first, with ties broken by name so the order is the same on every render.
+    fn sorted_teams(&self, guild_id: GuildId) -> Vec<(ChannelId, &Team)> {
+        let mut teams: Vec<(ChannelId, &Team)> = self.teams
+            .iter()
+            .filter(|(_, team)| team.role.guild_id == guild_id)
+            .map(|(channel_id, team)| (*channel_id, team))
+            .collect();
+        teams.sort_by(|(_, a), (_, b)| b.total_score().cmp(&a.total_score()).then_with(|| a.role.name.cmp(&b.role.name)));
+        teams
+    }
+
+    // Teams in a guild's manual order when one is set, anything it doesn't list follows in score order.
+    fn display_order(&self, guild_id: GuildId) -> Vec<(ChannelId, &Team)> {
+        let mut teams = self.sorted_teams(guild_id);
+        teams.sort_by_key(|(channel_id, _)| {
+            self.manual_order
+                .get(&guild_id)
+                .and_then(|order| order.iter().position(|ordered| ordered == channel_id))
+                .unwrap_or(usize::MAX)
+        });
+        teams
+    }
+
+    // 1-based positions in the score ranking, looked up when rows are shown in a different order.
+    fn ranks(&self, guild_id: GuildId) -> HashMap<ChannelId, usize> {
+        self.sorted_teams(guild_id)
+            .into_iter()
+            .enumerate()
+            .map(|(rank, (channel_id, _))| (channel_id, rank + 1))
+            .collect()
+    }
+
+    fn set_manual_order(&mut self, guild_id: GuildId, order: Option<Vec<ChannelId>>) {
+        self.touch();
+        match order {
+            Some(order) => self.manual_order.insert(guild_id, order),
+            None => self.manual_order.remove(&guild_id),
+        };
+    }
+
+    fn scoreboard_embed(&mut self, guild_id: GuildId, highlight: Option<ChannelId>) -> ScoreboardEmbed {
+        self.expire_bonuses();
+        if let Some(rendered) = self.rendered_embeds.get(&(guild_id, highlight)) {
+            return rendered.clone();
+        }
+
+        let rendered = self.render_scoreboard_embed(guild_id, highlight);
+        self.rendered_embeds.insert((guild_id, highlight), rendered.clone());
+        rendered
+    }
+
+    fn render_scoreboard_embed(&self, guild_id: GuildId, highlight: Option<ChannelId>) -> ScoreboardEmbed {
+        let ranks = self.ranks(guild_id);
+        let rows: Vec<ScoreboardRow> = self.display_order(guild_id)
+            .into_iter()
+            .map(|(channel_id, team)| ScoreboardRow {
+                rank: ranks[&channel_id],
+                name: team.display_name(),
+                role_id: team.role.id,
+                colour: team.role.colour,
+                score: team.total_score(),
+                bonus: team.bonus(),
+                highlighted: highlight == Some(channel_id),
+            })
+            .collect();
+        let total = rows.iter().fold(0i64, |total, row| total.saturating_add(row.score));
+
+        ScoreboardEmbed { rows: rows, total: total }
+    }
+
+    fn team_list_embed(&self, guild_id: GuildId, page: usize) -> TeamListEmbed {
+        let mut teams: Vec<(ChannelId, &Team)> = self.teams
+            .iter()
+            .filter(|(_, team)| team.role.guild_id == guild_id)
+            .map(|(channel_id, team)| (*channel_id, team))
+            .collect();
+        teams.sort_by(|(_, a), (_, b)| a.role.name.to_lowercase().cmp(&b.role.name.to_lowercase()));
+
+        let pages = ((teams.len() + EMBED_FIELD_LIMIT - 1) / EMBED_FIELD_LIMIT).max(1);
+        let page = page.max(1).min(pages);
+        let rows = teams
+            .iter()
+            .skip((page - 1) * EMBED_FIELD_LIMIT)
+            .take(EMBED_FIELD_LIMIT)
+            .map(|(channel_id, team)| TeamListRow {
+                name: team.display_name(),
+                channel_id: *channel_id,
+                colour: team.role.colour,
+                score: team.total_score(),
+            })
+            .collect();
+
+        TeamListEmbed { rows: rows, page: page, pages: pages, total: teams.len() }
+    }
+
+    fn render_scoreboard(&self, guild_id: GuildId, options: ScoreboardOptions) -> String {
+        let teams = self.display_order(guild_id);
+        if teams.is_empty() && !options.json {
+            return "No teams created".to_string();
+        }
+
+        // A manual order only changes which row comes first, ranks always follow the scores.
+        let ranks = self.ranks(guild_id);
+        if options.json {
+            let rows: Vec<_> = teams
+                .iter()
+                .map(|(channel_id, team)| serde_json::json!({
+                    "name": team.role.name,
+                    // Snowflakes don't fit in a javascript number, so they're passed around as strings.
+                    "role_id": team.role.id.to_string(),
+                    "score": team.total_score(),
+                    "bonus": team.bonus(),
+                    "rank": ranks[channel_id],
+                }))
+                .collect();
+
+            let json = serde_json::to_string_pretty(&rows).unwrap_or_else(|_| "[]".to_string());
+            return format!("```json\n{}\n```", json);
+        }
+
+        if options.compact {
+            return teams
+                .iter()
+                .map(|(channel_id, team)| format!("{}. {} — {}", ranks[channel_id], team.display_name(), team.total_score()))
+                .collect::<Vec<_>>()
+                .join("\n");
+        }
+
+        let mut total: i64 = 0;
+        let mut divisions: BTreeMap<&str, (Vec<String>, i64)> = BTreeMap::new();
+        for (channel_id, team) in &teams {
+            let division = match (options.by_division, &team.division) {
+                (true, Some(division)) => division.as_str(),
+                (true, None) => "Unassigned",
+                (false, _) => "",
+            };
+
+            let row = match team.bonus() {
+                0 => format!("{}: {}", team.display_name(), team.total_score()),
+                bonus => format!("{}: {} ({:+} bonus)", team.display_name(), team.total_score(), bonus),
+            };
+            let entry = divisions.entry(division).or_insert((Vec::new(), 0));
+            if options.highlight == Some(*channel_id) {
+                entry.0.push(format!("**{}** ◀", row));
+            } else {
+                entry.0.push(row);
+            }
+            entry.1 = entry.1.saturating_add(team.total_score());
+            total = total.saturating_add(team.total_score());
+        }
+
+        let footer = format!("{} teams · {} points total", teams.len(), total);
+        if !options.by_division {
+            let (score_list, _) = &divisions[""];
+            return format!("{}\n{}", score_list.join(", "), footer);
+        }
+
+        let mut lines = Vec::new();
+        for (division, (score_list, subtotal)) in &divisions {
+            lines.push(format!("**{}** ({} points): {}", division, subtotal, score_list.join(", ")));
+        }
+        lines.push(footer);
+        lines.join("\n")
+    }
+}
+
+// `percent` percent of `score`, rounded to whole points, or `None` if it doesn't fit in a score.
+fn percent_of(score: i64, percent: i64, rounding: Rounding) -> Option<i64> {
+    // Widened so the multiplication can't overflow before dividing back down.
+    let product = score as i128 * percent as i128;
+    let quotient = product.div_euclid(100);
+    let remainder = product.rem_euclid(100);
+
+    let rounded = match rounding {
+        Rounding::Down => quotient,
+        Rounding::Up if remainder > 0 => quotient + 1,
+        Rounding::Up => quotient,
+        Rounding::Nearest if remainder >= 50 => quotient + 1,
+        Rounding::Nearest => quotient,
+    };
+    i64::try_from(rounded).ok()
+}
+
+// Trailing teams that could still reach the leader's score if they took all of the `available` points.
+fn projected_contenders(scores: &[(String, i64)], available: i64) -> Option<(&(String, i64), Vec<(&(String, i64), i64, bool)>)> {
+    let leader = scores.iter().max_by_key(|(_, score)| *score)?;
+
+    let trailing = scores
+        .iter()
+        .filter(|entry| !std::ptr::eq(*entry, leader))
+        .map(|entry| {
+            let deficit = leader.1.saturating_sub(entry.1);
+            (entry, deficit, deficit <= available)
+        })
+        .collect();
+
+    Some((leader, trailing))
+}
+
+// Roles are named freely while channel names are lowercased and hyphenated, so compare them in the channel form.
+fn normalize_team_name(name: &str) -> String {
+    name.trim().to_lowercase().split_whitespace().collect::<Vec<_>>().join("-")
+}
+
+// Mottos end up in public announcements, so they're kept to one short line and can't form mentions even if
+// allowed mentions were ever loosened.
+fn sanitize_motto(text: &str) -> Result<String, String> {
+    let motto = text.split_whitespace().collect::<Vec<_>>().join(" ").replace('@', "@\u{200B}");
+    if motto.is_empty() {
+        Err("A motto can't be empty, use `/team motto clear` to remove one".to_string())
+    } else if motto.chars().count() > MOTTO_LIMIT {
+        Err(format!("A motto can be at most {} characters", MOTTO_LIMIT))
+    } else {
+        Ok(motto)
+    }
+}
+
+fn find_option<'a>(options: &'a [ApplicationCommandInteractionDataOption], name: &str) -> Option<&'a ApplicationCommandInteractionDataOptionValue> {
+    options
+        .iter()
+        .find(|option| option.name == name)
+        .and_then(|option| option.resolved.as_ref())
+}
+
+// Only the last few digits of an id, enough to tell ids apart without pasting them into a channel.
+fn redact_id(id: u64) -> String {
+    let id = id.to_string();
+    format!("…{}", &id[id.len().saturating_sub(4)..])
+}
+
+// One line per option, nested options indented beneath their subcommand.
+fn describe_options(options: &[ApplicationCommandInteractionDataOption], depth: usize, lines: &mut Vec<String>) {
+    for option in options {
+        let indent = "  ".repeat(depth);
+        let value = match &option.resolved {
+            None => "subcommand".to_string(),
+            Some(ApplicationCommandInteractionDataOptionValue::String(value)) => format!("string = {:?}", value),
+            Some(ApplicationCommandInteractionDataOptionValue::Integer(value)) => format!("integer = {}", value),
+            Some(ApplicationCommandInteractionDataOptionValue::Boolean(value)) => format!("boolean = {}", value),
+            Some(ApplicationCommandInteractionDataOptionValue::User(user, member)) => {
+                format!("user = {} ({}, member: {})", user.tag(), redact_id(user.id.0), member.is_some())
+            },
+            Some(ApplicationCommandInteractionDataOptionValue::Channel(channel)) => {
+                format!("channel = {:?} channel ({})", channel.kind, redact_id(channel.id.0))
+            },
+            Some(ApplicationCommandInteractionDataOptionValue::Role(role)) => format!("role = @{} ({})", role.name, redact_id(role.id.0)),
+            Some(_) => "unknown type".to_string(),
+        };
+
+        lines.push(format!("{}{}: {}", indent, option.name, value));
+        describe_options(&option.options, depth + 1, lines);
+    }
+}
+
+fn describe_interaction(data: &ApplicationCommandInteractionData) -> String {
+    let mut lines = vec![format!("/{} ({})", data.name, redact_id(data.id.0))];
+    describe_options(&data.options, 1, &mut lines);
+    lines.join("\n")
+}
+
+impl Handler {
+    fn new(config: &BotConfig, leaderboard: Leaderboard, teams: Arc<Mutex<Teams>>, questions: Questions, welcomed: Welcomed, guilds: SavedGuilds) -> Handler {
+        let host_roles: HashMap<GuildId, RoleId> = guilds.host_roles.into_iter().map(|(guild_id, role_id)| (GuildId(guild_id), role_id)).collect();
+        Handler {
+            teams: teams,
+            host_role_fixed: Arc::new(Mutex::new(host_roles.keys().copied().collect())),
+            host_role: Arc::new(Mutex::new(host_roles)),
+            configured_host_role: config.host_role_id.map(RoleId),
+            configs: Arc::new(Mutex::new(guilds.configs.into_iter().map(|(guild_id, config)| (GuildId(guild_id), config)).collect())),
+            guilds_path: guilds.path,
+            welcomed: Arc::new(Mutex::new(welcomed)),
+            active_games: Arc::new(Mutex::new(HashSet::new())),
+            role_edits: RoleEditQueue::new(),
+            player_points: Arc::new(Mutex::new(HashMap::new())),
+            leaderboard: Arc::new(Mutex::new(leaderboard)),
+            questions: Arc::new(Mutex::new(questions)),
+            active_questions: Arc::new(Mutex::new(HashMap::new())),
+            buzzers: Arc::new(Mutex::new(HashMap::new())),
+            last_interaction: Arc::new(Mutex::new(HashMap::new())),
+            score_watches: Arc::new(Mutex::new(HashMap::new())),
+            mimic: config.mimic_enabled,
+            force_command_sync: config.force_command_sync,
+            owner: config.owner_id.map(UserId),
+        }
+    }
+
+    fn is_owner(&self, interaction: &Interaction) -> bool {
+        let user_id = match (&interaction.member, &interaction.user) {
+            (Some(member), _) => member.user.id,
+            (None, Some(user)) => user.id,
+            (None, None) => return false,
+        };
+
+        self.owner == Some(user_id)
+    }
+
+    fn guild_config(&self, guild_id: Option<GuildId>) -> GuildConfig {
+        match guild_id {
+            Some(guild_id) => self.configs.lock().unwrap().get(&guild_id).cloned().unwrap_or_default(),
+            None => GuildConfig::default(),
+        }
+    }
+
+    // Whether scores may change right now, only false when the guild requires a game and none is running.
+    fn scoring_allowed(&self, guild_id: Option<GuildId>) -> bool {
+        match guild_id {
+            Some(guild_id) if self.guild_config(Some(guild_id)).require_active_game => self.active_games.lock().unwrap().contains(&guild_id),
+            _ => true,
+        }
+    }
+
+    // Whether the guild is playing cooperatively, with one shared score instead of team scores.
+    fn is_coop(&self, guild_id: Option<GuildId>) -> bool {
+        self.guild_config(guild_id).mode == GameMode::Coop
+    }
+
+    // Adds `delta` to the shared score of a cooperative game and describes the result.
+    fn adjust_coop_score(&self, guild_id: GuildId, delta: i64) -> String {
+        let config = self.guild_config(Some(guild_id));
+        let adjusted = self.teams.lock().unwrap().adjust_coop_score(guild_id, delta, &config);
+        let (score, clamped) = match adjusted {
+            Some(adjusted) => adjusted,
+            None => return format!("Cannot adjust shared score by {:+}, score out of range", delta),
+        };
+
+        let content = match config.coop_target {
+            Some(target) if score >= target => format!("Shared score adjusted by {:+} → {}, target of {} reached!", delta, score, target),
+            Some(target) => format!("Shared score adjusted by {:+} → {} of {}", delta, score, target),
+            None => format!("Shared score adjusted by {:+} → {}", delta, score),
+        };
+        if clamped {
+            format!("{} (capped at the maximum team score)", content)
+        } else {
+            content
+        }
+    }
+
+    // Edits every live `/team score watch` panel whose scoreboard changed since it was last shown, dropping expired ones.
+    async fn refresh_score_watches(&self, ctx: &Context) {
+        let stale: Vec<(UserId, ScoreWatch, String)> = {
+            // Teams are always locked before the watches, taking them the other way around can deadlock.
+            let mut teams = self.teams.lock().unwrap();
+            let mut watches = self.score_watches.lock().unwrap();
+            let now = Instant::now();
+            watches.retain(|_, watch| watch.expires > now);
+            if watches.is_empty() {
+                return
+            }
+
+            // Expiring bonuses bumps the version, so panels still showing them count as changed.
+            teams.expire_bonuses();
+            watches
+                .iter_mut()
+                .filter_map(|(user_id, watch)| {
+                    if watch.version == teams.version {
+                        return None
+                    }
+
+                    let scoreboard = teams.scoreboard(watch.guild_id, watch.options);
+                    watch.version = teams.version;
+                    Some((*user_id, watch.clone(), scoreboard))
+                })
+                .collect()
+        };
+
+        for (user_id, watch, scoreboard) in stale {
+            let content = if scoreboard.chars().count() > MESSAGE_LIMIT {
+                "Scoreboard is too long for a message, use `/team score list` instead".to_string()
+            } else {
+                scoreboard
+            };
+            let map = serde_json::json!({ "content": content, "allowed_mentions": { "parse": [] } });
+            if let Err(why) = ctx.http.edit_original_interaction_response(watch.application_id.0, &watch.token, &map).await {
+                // The token is most likely gone, there's nothing to tell the host since the panel can't be edited anymore.
+                warn!(%user_id, error = %why, "Cannot update score watch");
+                self.score_watches.lock().unwrap().remove(&user_id);
+            }
+        }
+    }
+
+    fn update_config(&self, guild_id: GuildId, f: impl FnOnce(&mut GuildConfig)) {
+        f(self.configs.lock().unwrap().entry(guild_id).or_default());
+        self.save_guilds();
+    }
+
+    // Writes every guild's config and fixed host role, so neither has to be set up again after a restart.
+    fn save_guilds(&self) {
+        let fixed = self.host_role_fixed.lock().unwrap().clone();
+        let saved = SavedGuilds {
+            path: PathBuf::new(),
+            configs: self.configs.lock().unwrap().iter().map(|(guild_id, config)| (guild_id.0, config.clone())).collect(),
+            host_roles: self.host_role.lock().unwrap()
+                .iter()
+                .filter(|(guild_id, _)| fixed.contains(guild_id))
+                .map(|(guild_id, role_id)| (guild_id.0, *role_id))
+                .collect(),
+        };
+
+        if let Err(why) = save_json_atomic(&self.guilds_path, &saved) {
+            error!(error = %why, "Cannot save guild settings");
+        }
+    }
+
+    fn guild_host_role(&self, guild_id: GuildId) -> Option<RoleId> {
+        self.host_role.lock().unwrap().get(&guild_id).copied()
+    }
+
+    // Finds the host role of a guild from its full list of roles, which every guild comes with in `guild_create`, both
+    // on startup and whenever the bot joins one. A role picked with `/host set` stays put.
+    fn resolve_host_role(&self, guild_id: GuildId, roles: &HashMap<RoleId, Role>) {
+        if self.host_role_fixed.lock().unwrap().contains(&guild_id) {
+            return
+        }
+
+        if let Some(role_id) = self.configured_host_role.filter(|role_id| roles.contains_key(role_id)) {
+            self.fix_host_role(guild_id, role_id);
+            return
+        }
+
+        let mut host_roles = self.host_role.lock().unwrap();
+        match roles.values().find(|role| role.name == DEFAULT_HOST_ROLE_NAME) {
+            Some(role) => {
+                host_roles.insert(guild_id, role.id);
+            },
+            None => {
+                host_roles.remove(&guild_id);
+                warn!(%guild_id, "No host role found, create a role named {} or pick one with /host set", DEFAULT_HOST_ROLE_NAME);
+            },
+        }
+    }
+
+    // Uses `role_id` as the host role of `guild_id` from now on, rather than whichever role is named "Host".
+    fn fix_host_role(&self, guild_id: GuildId, role_id: RoleId) {
+        self.host_role.lock().unwrap().insert(guild_id, role_id);
+        self.host_role_fixed.lock().unwrap().insert(guild_id);
+        self.save_guilds();
+    }
+
+    // Server administrators, going by the permissions Discord resolved for the invoker.
+    fn is_administrator(&self, interaction: &Interaction) -> bool {
+        interaction.member
+            .as_ref()
+            .and_then(|member| member.permissions)
+            .map_or(false, |permissions| permissions.contains(Permissions::ADMINISTRATOR))
+    }
+
+    async fn is_host(&self, ctx: &Context, interaction: &Interaction) -> bool {
+        let host_role = interaction.guild_id.and_then(|guild_id| self.guild_host_role(guild_id));
+        match (&interaction.member, interaction.guild_id, host_role) {
+            // Interactions carry the invoker's roles, so there's nothing to look up when we have them.
+            (Some(member), _, Some(host_role)) if member.roles.contains(&host_role) => true,
+            (Some(member), Some(guild_id), _) => self.user_is_host(ctx, guild_id, &member.user).await,
+            _ => false,
+        }
+    }
+
+    async fn user_is_host(&self, ctx: &Context, guild_id: GuildId, user: &User) -> bool {
+        let host_role = match self.guild_host_role(guild_id) {
+            Some(host_role) => host_role,
+            None => return false,
+        };
+
+        // Checks the cached member first and asks Discord when it isn't cached, if both fail we deny.
+        match user.has_role(ctx, guild_id, host_role).await {
+            Ok(has_role) => has_role,
+            Err(why) => {
+                warn!(%guild_id, user_id = %user.id, error = %why, "Cannot check host role");
+                false
+            },
+        }
+    }
+
+    fn is_ephemeral(&self, guild_id: Option<GuildId>, category: CommandCategory) -> bool {
+        match category {
+            CommandCategory::Admin => self.guild_config(guild_id).ephemeral_admin,
+            CommandCategory::Game => false,
+            CommandCategory::Private => true,
+        }
+    }
+
+    // Sends the response of a command, `deferred` being whether it was deferred with `try_defer` and if so how.
+    async fn respond(&self, ctx: &Context, interaction: &Interaction, response: Response, ephemeral: bool, deferred: Option<bool>) {
+        // Discord rejects messages over the content limit, so long responses (huge scoreboards) go out as a file instead.
+        // Files are posted to the channel for everyone to see, so private responses are cut short instead.
+        let response = match response {
+            Response::Text(content) if ephemeral && content.chars().count() > MESSAGE_LIMIT => Response::Text(truncate_message(&content, MESSAGE_LIMIT)),
+            Response::Text(content) if content.chars().count() > MESSAGE_LIMIT => {
+                let notice = Response::Text("Response is too long for a message, attached as a file".to_string());
+                if let Err(why) = deliver(ctx, interaction, &notice, ephemeral, deferred).await {
+                    error!(guild_id = ?interaction.guild_id, channel_id = ?interaction.channel_id, user_id = ?interaction_user_id(interaction), error = %why, "Cannot respond to slash command");
+                }
+
+                if let Some(channel_id) = interaction.channel_id {
+                    let files = vec![AttachmentType::Bytes {
+                        data: Cow::from(content.into_bytes()),
+                        filename: "response.txt".to_string(),
+                    }];
+
+                    if let Err(why) = channel_id.send_files(&ctx.http, files, |message| message).await {
+                        error!(guild_id = ?interaction.guild_id, %channel_id, error = %why, "Cannot send response file");
+                    }
+                }
+                return;
+            },
+            response => response,
+        };
+
+        if let Err(why) = deliver(ctx, interaction, &response, ephemeral, deferred).await {
+            error!(guild_id = ?interaction.guild_id, channel_id = ?interaction.channel_id, user_id = ?interaction_user_id(interaction), error = %why, "Cannot respond to slash command");
+            return;
+        }
+
+        if let Response::Swatch(_, colour) = response {
+            send_swatch(ctx, interaction, colour).await;
+        }
+    }
+
+    // Runs a slash command and returns the response. Interactions are whatever Discord sends us, so anything that
+    // doesn't have the shape we registered is an error for the invoker rather than a panic.
+    async fn run_command(&self, ctx: &Context, interaction: &Interaction, data: &ApplicationCommandInteractionData) -> Result<Response, CommandError> {
+        let content = match data.name.as_str() {
+            "ping" => "pong".to_string(),
+            "id" => {
+                let options = data
+                    .options
+                    .get(0)
+                    .and_then(|option| option.resolved.as_ref())
+                    .ok_or(CommandError::MissingOption("user"))?;
+
+                if let ApplicationCommandInteractionDataOptionValue::User(user, _member) =
+                    options
+                {
+                    format!("{}'s id is {}", user.tag(), user.id)
+                } else {
+                    "Please provide a valid user".to_string()
+                }
+            },
+            "team" => {
+                let suboption = data.options.get(0).ok_or(CommandError::MissingOption("subcommand"))?;
+                match suboption.name.as_str() {
+                    "rename" => {
+                        let name_arg = suboption
+                            .options
+                            .get(0)
+                            .and_then(|option| option.resolved.as_ref())
+                            .ok_or(CommandError::MissingOption("name"))?;
+
+                        match (name_arg, interaction.channel_id) {
+                            (ApplicationCommandInteractionDataOptionValue::String(new_name), Some(channel_id)) => {
+                                {
+                                    let teams = self.teams.lock().unwrap().get_team(&channel_id);
+                                    match teams {
+                                        Some(team) => {
+                                            match self.role_edits.edit(&ctx.http, &team.role, |r| {
+                                                r.name(new_name);
+                                                r
+                                            }).await {
+                                                    Ok(role) => {
+                                                        self.with_team(&channel_id, |team| team.role = role);
+                                                        format!("Team name is now {}", new_name)
+                                                    },
+                                                    Err(err) => format!("Failed to rename team: {:?}", err),
+                                                }
+                                        },
+                                        _ => "Failed to rename team, could not find team".to_string(),
+                                    }
+                                }
+                            },
+                            _ => "Failed to rename team, invalid argument or channel id".to_string()
+                        }
+                    },
+                    "recolor" => {
+                        let new_color = match find_option(&suboption.options, "hex") {
+                            Some(ApplicationCommandInteractionDataOptionValue::String(hex)) => match color::parse_hex(hex) {
+                                Some(colour) => colour,
+                                None => return Ok(format!("{} is not a hex color, expected six hex digits like #1abc9c", hex.trim()).into()),
+                            },
+                            _ => {
+                                let mut components = Vec::new();
+                                for name in &["red", "green", "blue"] {
+                                    match find_option(&suboption.options, name) {
+                                        Some(ApplicationCommandInteractionDataOptionValue::Integer(component)) => components.push((*name, *component)),
+                                        _ => return Ok("Give either `hex` or all of `red`, `green` and `blue`".to_string().into()),
+                                    }
+                                }
+
+                                // Casting would silently wrap, 300 red coming out as 44.
+                                let out_of_range: Vec<String> = components
+                                    .iter()
+                                    .filter(|(_, component)| u8::try_from(*component).is_err())
+                                    .map(|(name, component)| format!("{} ({})", name, component))
+                                    .collect();
+                                if !out_of_range.is_empty() {
+                                    return Ok(format!("Color components must be between 0 and 255, got {}", out_of_range.join(", ")).into());
+                                }
+                                Colour::from_rgb(components[0].1 as u8, components[1].1 as u8, components[2].1 as u8)
+                            },
+                        };
+
+                        match interaction.channel_id {
+                            Some(channel_id) => {
+                                {
+                                    let teams = self.teams.lock().unwrap().get_team(&channel_id);
+                                    match teams {
+                                        Some(team) => {
+                                            match self.role_edits.edit(&ctx.http, &team.role, |r| {
+                                                r.colour(new_color.0 as u64);
+                                                r
+                                            }).await {
+                                                Ok(role) => {
+                                                    self.with_team(&channel_id, |team| {
+                                                        team.previous_colour = Some(team.role.colour);
+                                                        team.role = role;
+                                                    });
+                                                    let content = format!("Team color is now ({}, {}, {})", new_color.r(), new_color.g(), new_color.b());
+                                                    return Ok(Response::Swatch(content, new_color))
+                                                },
+                                                Err(err) => format!("Failed to rename team: {:?}", err),
+                                            }
+                                        },
+                                        _ => "Failed to rename team, could not find team".to_string(),
+                                    }
+                                }
+                            },
+                            _ => "Failed to rename team, invalid argument or channel id".to_string()
+                        }
+                    },
+                    "color" => {
+                        let color_options = suboption.options.get(0).ok_or(CommandError::MissingOption("subcommand"))?;
+                        match (color_options.name.as_str(), interaction.guild_id) {
+                            ("contrast-check", Some(guild_id)) => {
+                                let mut teams: Vec<(String, Colour)> = self.teams.lock().unwrap().teams
+                                    .values()
+                                    // Roles without a color show up in the default text color, which is always readable.
+                                    .filter(|team| team.role.guild_id == guild_id && team.role.colour.0 != 0)
+                                    .map(|team| (team.display_name(), team.role.colour))
+                                    .collect();
+                                teams.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+                                let mut warnings = Vec::new();
+                                for (name, colour) in &teams {
+                                    for (theme, background) in [("dark", color::DARK_THEME), ("light", color::LIGHT_THEME)].iter() {
+                                        let ratio = color::contrast_ratio(*colour, *background);
+                                        if ratio < BACKGROUND_CONTRAST {
+                                            let suggestion = color::increase_contrast(*colour, *background, BACKGROUND_CONTRAST)
+                                                .map(|suggestion| format!(", try #{}", suggestion.hex()))
+                                                .unwrap_or_default();
+                                            warnings.push(format!("**{}** (#{}) is hard to read on the {} theme ({:.1}:1){}", name, colour.hex(), theme, ratio, suggestion));
+                                        }
+                                    }
+                                }
+
+                                for (index, (name, colour)) in teams.iter().enumerate() {
+                                    for (other_name, other_colour) in &teams[index + 1..] {
+                                        let ratio = color::contrast_ratio(*colour, *other_colour);
+                                        if ratio < TEAM_CONTRAST {
+                                            let suggestion = color::increase_contrast(*other_colour, *colour, TEAM_CONTRAST)
+                                                .map(|suggestion| format!(", try #{} for {}", suggestion.hex(), other_name))
+                                                .unwrap_or_default();
+                                            warnings.push(format!("**{}** and **{}** are hard to tell apart ({:.1}:1){}", name, other_name, ratio, suggestion));
+                                        }
+                                    }
+                                }
+
+                                if teams.is_empty() {
+                                    "No teams with a color to check".to_string()
+                                } else if warnings.is_empty() {
+                                    format!("All {} team colors have enough contrast", teams.len())
+                                } else {
+                                    warnings.join("\n")
+                                }
+                            },
+                            (_, None) => "No guild for interaction".to_string(),
+                            _ => "Invalid team->color suboption".to_string(),
+                        }
+                    },
+                    "copy-color" => {
+                        if !self.is_host(&ctx, &interaction).await {
+                            return Err(CommandError::PermissionDenied)
+                        } else {
+                            match (find_option(&suboption.options, "from"), find_option(&suboption.options, "to")) {
+                                (Some(ApplicationCommandInteractionDataOptionValue::Channel(from)),
+                                Some(ApplicationCommandInteractionDataOptionValue::Channel(to))) => {
+                                    let (from_team, to_team) = {
+                                        let mut teams = self.teams.lock().unwrap();
+                                        (teams.get_team(&from.id), teams.get_team(&to.id))
+                                    };
+
+                                    match (from_team, to_team) {
+                                        (Some(from_team), Some(to_team)) => {
+                                            let colour = from_team.role.colour;
+                                            match self.role_edits.edit(&ctx.http, &to_team.role, |r| {
+                                                r.colour(colour.0 as u64);
+                                                r
+                                            }).await {
+                                                Ok(role) => {
+                                                    self.with_team(&to.id, |team| {
+                                                        team.previous_colour = Some(team.role.colour);
+                                                        team.role = role;
+                                                    });
+                                                    format!("{} now uses {}'s color #{}", to_team.role.name, from_team.role.name, colour.hex())
+                                                },
+                                                Err(err) => format!("Failed to copy team color: {:?}", err),
+                                            }
+                                        },
+                                        _ => "Failed to copy team color, could not find both teams".to_string(),
+                                    }
+                                },
+                                _ => "Failed to copy team color, invalid channels".to_string(),
+                            }
+                        }
+                    },
+                    "archive" | "unarchive" => {
+                        if !self.is_host(&ctx, &interaction).await {
+                            return Err(CommandError::PermissionDenied)
+                        } else {
+                            match find_option(&suboption.options, "channel") {
+                                Some(ApplicationCommandInteractionDataOptionValue::Channel(partial_channel)) => {
+                                    let mut teams = self.teams.lock().unwrap();
+                                    if suboption.name == "archive" {
+                                        match teams.archive(&partial_channel.id) {
+                                            Some(team) => format!("Archived **{}** with {} points, restore it with `/team unarchive`", team.display_name(), team.score),
+                                            None => "Failed to archive team, could not find team".to_string(),
+                                        }
+                                    } else {
+                                        match teams.unarchive(&partial_channel.id) {
+                                            Some(Ok(team)) => format!("Restored **{}** with {} points", team.display_name(), team.score),
+                                            Some(Err(name)) => format!("Cannot restore {}, its channel or role already belongs to another team", name),
+                                            None => "Failed to unarchive team, no archived team for that channel".to_string(),
+                                        }
+                                    }
+                                },
+                                _ => format!("Failed to {} team, invalid channel", suboption.name),
+                            }
+                        }
+                    },
+                    "swap-scores" => {
+                        if !self.is_host(&ctx, &interaction).await {
+                            return Err(CommandError::PermissionDenied)
+                        } else if !self.scoring_allowed(interaction.guild_id) {
+                            return Err(CommandError::NoActiveGame)
+                        } else if self.is_coop(interaction.guild_id) {
+                            return Err(CommandError::CoopMode)
+                        } else {
+                            match (find_option(&suboption.options, "a"), find_option(&suboption.options, "b")) {
+                                (Some(ApplicationCommandInteractionDataOptionValue::Channel(a)),
+                                Some(ApplicationCommandInteractionDataOptionValue::Channel(b))) => {
+                                    // Both scores change under one lock, so nothing can adjust either team halfway through.
+                                    match self.teams.lock().unwrap().swap_scores(&a.id, &b.id) {
+                                        Some(((a_name, a_score), (b_name, b_score))) => {
+                                            format!("Swapped scores! **{}** → {}, **{}** → {}", a_name, a_score, b_name, b_score)
+                                        },
+                                        None => "Failed to swap scores, pick two different teams".to_string(),
+                                    }
+                                },
+                                _ => "Failed to swap scores, invalid channels".to_string(),
+                            }
+                        }
+                    },
+                    "recolor-revert" => {
+                        match find_option(&suboption.options, "channel") {
+                            Some(ApplicationCommandInteractionDataOptionValue::Channel(partial_channel)) => {
+                                let team = self.teams.lock().unwrap().get_team(&partial_channel.id);
+                                match team {
+                                    Some(Team { previous_colour: Some(previous_colour), role, .. }) => {
+                                        match self.role_edits.edit(&ctx.http, &role, |r| {
+                                            r.colour(previous_colour.0 as u64);
+                                            r
+                                        }).await {
+                                            Ok(role) => {
+                                                self.with_team(&partial_channel.id, |team| {
+                                                    team.previous_colour = Some(team.role.colour);
+                                                    team.role = role;
+                                                });
+                                                return Ok(Response::Swatch(format!("Team color restored to #{}", previous_colour.hex()), previous_colour))
+                                            },
+                                            Err(err) => format!("Failed to revert team color: {:?}", err),
+                                        }
+                                    },
+                                    Some(_) => "No previous color recorded for this team".to_string(),
+                                    None => "Failed to revert team color, could not find team".to_string(),
+                                }
+                            },
+                            _ => "Failed to revert team color, invalid channel".to_string(),
+                        }
+                    },
+                    "create" => {
+                        self.require_host_role(interaction.guild_id)?;
+                        if !self.is_host(ctx, interaction).await {
+                            return Err(CommandError::PermissionDenied)
+                        } else {
+                            let channel_arg = find_option(&suboption.options, "channel").ok_or(CommandError::MissingOption("channel"))?;
+                            let role_arg = find_option(&suboption.options, "role").ok_or(CommandError::MissingOption("role"))?;
+
+                            match (channel_arg, role_arg) {
+                                (ApplicationCommandInteractionDataOptionValue::Channel(partial_channel),
+                                ApplicationCommandInteractionDataOptionValue::Role(role)) => {
+                                    match self.create_team(partial_channel.id, role.clone()) {
+                                        Ok(()) => "Created new team".to_string(),
+                                        Err(CreateTeamError::ChannelTaken) => format!("{} already has a team", partial_channel.id.mention()),
+                                        Err(CreateTeamError::RoleTaken(channel_id)) => format!("A team for {} already exists in {}", role.name, channel_id.mention()),
+                                    }
+                                },
+                                _ => "Failed to create team, unknown channel or role".to_string(),
+                            }
+                        }
+                    },
+                    "score" => {
+                        let score_options = suboption.options.get(0).ok_or(CommandError::MissingOption("subcommand"))?;
+                        match score_options.name.as_str() {
+                            "list" => {
+                                let guild_id = match interaction.guild_id {
+                                    Some(guild_id) => guild_id,
+                                    None => return Ok(Response::Text("No guild for interaction".to_string())),
+                                };
+
+                                let by_division = match find_option(&score_options.options, "by_division") {
+                                    Some(ApplicationCommandInteractionDataOptionValue::Boolean(by_division)) => *by_division,
+                                    _ => false,
+                                };
+
+                                let compact = match find_option(&score_options.options, "compact") {
+                                    Some(ApplicationCommandInteractionDataOptionValue::Boolean(compact)) => *compact,
+                                    _ => false,
+                                };
+
+                                let json = match find_option(&score_options.options, "format") {
+                                    Some(ApplicationCommandInteractionDataOptionValue::String(format)) => format == "json",
+                                    _ => false,
+                                };
+
+                                let mut teams = self.teams.lock().unwrap();
+                                let highlight = interaction.member.as_ref().and_then(|member| teams.team_for_roles(&member.roles));
+                                // The plain ranking is an embed, the other layouts are text so they stay copyable.
+                                if !by_division && !compact && !json {
+                                    return Ok(Response::Scoreboard(teams.scoreboard_embed(guild_id, highlight)));
+                                }
+
+                                teams.scoreboard(guild_id, ScoreboardOptions {
+                                    by_division: by_division,
+                                    compact: compact,
+                                    json: json,
+                                    highlight: highlight,
+                                })
+                            },
+                            "watch" => {
+                                if !self.is_host(&ctx, &interaction).await {
+                                    return Err(CommandError::PermissionDenied)
+                                } else {
+                                    match (&interaction.member, interaction.guild_id) {
+                                        (Some(member), Some(guild_id)) => {
+                                            let compact = match find_option(&score_options.options, "compact") {
+                                                Some(ApplicationCommandInteractionDataOptionValue::Boolean(compact)) => *compact,
+                                                _ => false,
+                                            };
+
+                                            let (options, scoreboard, version) = {
+                                                let mut teams = self.teams.lock().unwrap();
+                                                let options = ScoreboardOptions {
+                                                    by_division: false,
+                                                    compact: compact,
+                                                    json: false,
+                                                    highlight: teams.team_for_roles(&member.roles),
+                                                };
+                                                (options, teams.scoreboard(guild_id, options), teams.version)
+                                            };
+
+                                            // Replaces any earlier panel, only the newest one keeps updating.
+                                            self.score_watches.lock().unwrap().insert(member.user.id, ScoreWatch {
+                                                guild_id: guild_id,
+                                                application_id: interaction.application_id,
+                                                token: interaction.token.clone(),
+                                                options: options,
+                                                version: version,
+                                                expires: Instant::now() + SCORE_WATCH_DURATION,
+                                            });
+                                            scoreboard
+                                        },
+                                        (None, _) => "No member for interaction".to_string(),
+                                        (_, None) => "No guild for interaction".to_string(),
+                                    }
+                                }
+                            },
+                            "adjust" => {
+                                self.require_host_role(interaction.guild_id)?;
+                                if !self.is_host(ctx, interaction).await {
+                                    return Err(CommandError::PermissionDenied)
+                                } else if !self.scoring_allowed(interaction.guild_id) {
+                                    return Err(CommandError::NoActiveGame)
+                                } else if self.is_coop(interaction.guild_id) {
+                                    return Err(CommandError::CoopMode)
+                                } else {
+                                    let (content, applied) = self.adjust_scores(interaction.guild_id, interaction.channel_id, &score_options.options);
+                                    if let Some(guild_id) = interaction.guild_id {
+                                        self.credit_members(ctx, guild_id, &applied).await;
+                                    }
+                                    content
+                                }
+                            }
+                            "batch" => {
+                                if !self.is_host(&ctx, &interaction).await {
+                                    return Err(CommandError::PermissionDenied)
+                                } else if !self.scoring_allowed(interaction.guild_id) {
+                                    return Err(CommandError::NoActiveGame)
+                                } else if self.is_coop(interaction.guild_id) {
+                                    return Err(CommandError::CoopMode)
+                                } else {
+                                    match (find_option(&score_options.options, "adjustments"), interaction.guild_id) {
+                                        (Some(ApplicationCommandInteractionDataOptionValue::String(adjustments)), Some(guild_id)) => {
+                                            let config = self.guild_config(Some(guild_id));
+                                            let (summary, credited) = {
+                                                let mut teams = self.teams.lock().unwrap();
+                                                teams.touch();
+
+                                                let mut summary = Vec::new();
+                                                let mut credited = Vec::new();
+                                                // Slash command strings can't contain newlines, so entries may also be split with `;` or `,`.
+                                                for entry in adjustments.split(|c| c == '\n' || c == ';' || c == ',').map(str::trim).filter(|entry| !entry.is_empty()) {
+                                                    let (reference, delta) = match entry.rsplitn(2, char::is_whitespace).collect::<Vec<_>>().as_slice() {
+                                                        [delta, reference] => (reference.trim(), delta.trim_start_matches('+').parse::<i64>()),
+                                                        _ => {
+                                                            summary.push(format!("Skipped `{}`, expected `team amount`", entry));
+                                                            continue;
+                                                        },
+                                                    };
+
+                                                    match (teams.resolve_team_ref(guild_id, reference), delta) {
+                                                        (Some(channel_id), Ok(delta)) => {
+                                                            let delta = match apply_score_step(delta, &config) {
+                                                                Ok((delta, _)) => delta,
+                                                                Err(step) => {
+                                                                    summary.push(format!("Skipped `{}`, not a multiple of {}", entry, step));
+                                                                    continue;
+                                                                },
+                                                            };
+                                                            let team = match teams.teams.get_mut(&channel_id) {
+                                                                Some(team) => team,
+                                                                None => {
+                                                                    summary.push(format!("Skipped `{}`, unknown team", entry));
+                                                                    continue;
+                                                                },
+                                                            };
+                                                            let (score, clamped) = match add_to_score(team.score, delta, &config) {
+                                                                Some(adjusted) => adjusted,
+                                                                None => {
+                                                                    summary.push(format!("Skipped `{}`, score out of range", entry));
+                                                                    continue;
+                                                                },
+                                                            };
+                                                            let applied = score.saturating_sub(team.score);
+                                                            team.score = score;
+                                                            summary.push(format!(
+                                                                "**{}** {:+} → {}{}",
+                                                                team.display_name(),
+                                                                delta,
+                                                                team.score,
+                                                                if clamped { " (capped)" } else { "" },
+                                                            ));
+                                                            credited.push((team.role.id, applied));
+                                                            teams.record_delta(guild_id, applied);
+                                                        },
+                                                        (None, _) => summary.push(format!("Skipped `{}`, unknown team", entry)),
+                                                        (_, Err(_)) => summary.push(format!("Skipped `{}`, invalid amount", entry)),
+                                                    }
+                                                }
+                                                (summary, credited)
+                                            };
+                                            self.credit_members(ctx, guild_id, &credited).await;
+
+                                            if summary.len() == 0 {
+                                                "No adjustments given".to_string()
+                                            } else {
+                                                summary.join("\n")
+                                            }
+                                        },
+                                        _ => "Failed to apply batch, invalid adjustments or guild id".to_string(),
+                                    }
+                                }
+                            },
+                            "adjust-percent" => {
+                                if !self.is_host(&ctx, &interaction).await {
+                                    return Err(CommandError::PermissionDenied)
+                                } else if !self.scoring_allowed(interaction.guild_id) {
+                                    return Err(CommandError::NoActiveGame)
+                                } else if self.is_coop(interaction.guild_id) {
+                                    return Err(CommandError::CoopMode)
+                                } else {
+                                    let team = match find_option(&score_options.options, "team") {
+                                        Some(ApplicationCommandInteractionDataOptionValue::Channel(partial_channel)) => Some(partial_channel.id),
+                                        _ => interaction.channel_id,
+                                    };
+
+                                    match (find_option(&score_options.options, "percent"), team) {
+                                        (Some(ApplicationCommandInteractionDataOptionValue::Integer(percent)), Some(channel_id)) => {
+                                            let (content, applied) = self.adjust_score_percent(interaction.guild_id, channel_id, *percent);
+                                            if let Some(guild_id) = interaction.guild_id {
+                                                self.credit_members(&ctx, guild_id, &applied).await;
+                                            }
+                                            content
+                                        },
+                                        _ => "Failed to adjust, invalid percentage or team".to_string(),
+                                    }
+                                }
+                            },
+                            "temp-bonus" => {
+                                if !self.is_host(&ctx, &interaction).await {
+                                    return Err(CommandError::PermissionDenied)
+                                } else if !self.scoring_allowed(interaction.guild_id) {
+                                    return Err(CommandError::NoActiveGame)
+                                } else if self.is_coop(interaction.guild_id) {
+                                    return Err(CommandError::CoopMode)
+                                } else {
+                                    let team = match find_option(&score_options.options, "team") {
+                                        Some(ApplicationCommandInteractionDataOptionValue::Channel(partial_channel)) => Some(partial_channel.id),
+                                        _ => interaction.channel_id,
+                                    };
+                                    let amount = find_option(&score_options.options, "amount");
+                                    let seconds = match find_option(&score_options.options, "seconds") {
+                                        Some(ApplicationCommandInteractionDataOptionValue::Integer(seconds)) if *seconds > 0 => Some(*seconds),
+                                        _ => None,
+                                    };
+                                    let expires = seconds.and_then(|seconds| Instant::now().checked_add(Duration::from_secs(seconds as u64)));
+
+                                    match (amount, seconds, expires, team) {
+                                        (Some(ApplicationCommandInteractionDataOptionValue::Integer(amount)), Some(seconds), Some(expires), Some(channel_id)) => {
+                                            match apply_score_step(*amount, &self.guild_config(interaction.guild_id)) {
+                                                Ok((amount, _)) => {
+                                                    let config = self.guild_config(interaction.guild_id);
+                                                    let result = self.with_team(&channel_id, |team| {
+                                                        // The cap applies to the score with the bonus, so the bonus shrinks to fit under it.
+                                                        let (total, clamped) = match add_to_score(team.score, amount, &config) {
+                                                            Some(adjusted) => adjusted,
+                                                            None => return format!("Cannot grant **{}** a {:+} bonus, score out of range", team.display_name(), amount),
+                                                        };
+                                                        let bonus = total - team.score;
+                                                        team.temp_bonus = Some((bonus, expires));
+                                                        let content = format!("**{}** gets a {:+} bonus for {} seconds → {}", team.display_name(), bonus, seconds, team.total_score());
+                                                        if clamped {
+                                                            format!("{} (capped at the maximum team score)", content)
+                                                        } else {
+                                                            content
+                                                        }
+                                                    });
+                                                    result.unwrap_or_else(|| "Missing team, could not grant bonus".to_string())
+                                                },
+                                                Err(step) => format!("Cannot grant a {:+} bonus, adjustments must be a multiple of {}", amount, step),
+                                            }
+                                        },
+                                        _ => "Failed to grant bonus, invalid amount, duration or team".to_string(),
+                                    }
+                                }
+                            },
+                            "reset" => {
+                                self.require_host_role(interaction.guild_id)?;
+                                if !self.is_host(ctx, interaction).await {
+                                    return Err(CommandError::PermissionDenied)
+                                } else if !self.scoring_allowed(interaction.guild_id) {
+                                    return Err(CommandError::NoActiveGame)
+                                } else {
+                                    match interaction.guild_id {
+                                        Some(guild_id) => {
+                                            let count = self.teams.lock().unwrap().reset_all(guild_id);
+                                            format!("Reset the scores of all {} teams in this server to 0, teams in every channel are included", count)
+                                        },
+                                        None => "Failed to reset scores, invalid guild id".to_string(),
+                                    }
+                                }
+                            },
+                            "set-all" => {
+                                if !self.is_host(&ctx, &interaction).await {
+                                    return Err(CommandError::PermissionDenied)
+                                } else if !self.scoring_allowed(interaction.guild_id) {
+                                    return Err(CommandError::NoActiveGame)
+                                } else if self.is_coop(interaction.guild_id) {
+                                    return Err(CommandError::CoopMode)
+                                } else {
+                                    match (find_option(&score_options.options, "value"), interaction.guild_id) {
+                                        (Some(ApplicationCommandInteractionDataOptionValue::Integer(value)), Some(guild_id)) => {
+                                            let config = self.guild_config(Some(guild_id));
+                                            match apply_score_step(*value, &config) {
+                                                Ok((value, _)) => {
+                                                    let (value, clamped) = clamp_score(value, &config);
+                                                    let count = self.teams.lock().unwrap().set_all(guild_id, value);
+                                                    if clamped {
+                                                        format!("Set the score of {} teams to {} (capped at the maximum team score)", count, value)
+                                                    } else {
+                                                        format!("Set the score of {} teams to {}", count, value)
+                                                    }
+                                                },
+                                                Err(step) => format!("Cannot set scores to {}, scores must be a multiple of {}", value, step),
+                                            }
+                                        },
+                                        _ => "Failed to set scores, invalid value or guild id".to_string(),
+                                    }
+                                }
+                            },
+                            "projected-winner" => {
+                                let remaining = match find_option(&score_options.options, "remaining") {
+                                    Some(ApplicationCommandInteractionDataOptionValue::Integer(remaining)) => Some(*remaining),
+                                    _ => None,
+                                };
+                                let max_points = match find_option(&score_options.options, "max_points") {
+                                    Some(ApplicationCommandInteractionDataOptionValue::Integer(max_points)) => Some(*max_points),
+                                    _ => None,
+                                };
+
+                                let guild_id = match interaction.guild_id {
+                                    Some(guild_id) => guild_id,
+                                    None => return Ok(Response::Text("Failed to project winner, invalid guild id".to_string())),
+                                };
+
+                                // The questions left in the bank are what's still up for grabs, unless the host says otherwise.
+                                let (available, left) = match (remaining, max_points) {
+                                    (Some(remaining), Some(max_points)) => {
+                                        (remaining.saturating_mul(max_points), format!("{} questions left (max {} each)", remaining, max_points))
+                                    },
+                                    (Some(_), None) => return Ok(Response::Text("Pass `max_points` along with `remaining`".to_string())),
+                                    (None, _) => {
+                                        let config = self.guild_config(Some(guild_id));
+                                        let difficulties = self.questions.lock().unwrap().remaining_difficulties(guild_id);
+                                        let available = difficulties
+                                            .iter()
+                                            .fold(0i64, |available, difficulty| available.saturating_add(config.question_points(*difficulty)));
+                                        (available, format!("{} questions left worth {} points", difficulties.len(), available))
+                                    },
+                                };
+
+                                let scores: Vec<(String, i64)> = self.teams.lock().unwrap().teams
+                                    .values()
+                                    .filter(|team| team.role.guild_id == guild_id)
+                                    .map(|team| (team.display_name(), team.total_score()))
+                                    .collect();
+
+                                match projected_contenders(&scores, available) {
+                                    Some((leader, trailing)) => {
+                                        let mut lines = vec![format!("**{}** leads with {}", leader.0, leader.1)];
+                                        let mut contenders = 0;
+                                        for ((name, _), deficit, can_win) in trailing {
+                                            if can_win {
+                                                contenders += 1;
+                                                lines.push(format!("{} trails by {}; with {} they can still win", name, deficit, left));
+                                            } else {
+                                                lines.push(format!("{} trails by {} and can no longer catch up", name, deficit));
+                                            }
+                                        }
+
+                                        if contenders == 0 {
+                                            lines.push(format!("Game decided, {} wins", leader.0));
+                                        }
+                                        lines.join("\n")
+                                    },
+                                    None => "No teams created".to_string(),
+                                }
+                            },
+                            _ => {
+                                "Invalid team->score suboption".to_string()
+                            }
+                        }
+                    },
+                    "division" => {
+                        let division_options = suboption.options.get(0).ok_or(CommandError::MissingOption("subcommand"))?;
+                        match division_options.name.as_str() {
+                            "set" => {
+                                if !self.is_host(&ctx, &interaction).await {
+                                    return Err(CommandError::PermissionDenied)
+                                } else {
+                                    let channel_arg = find_option(&division_options.options, "channel");
+                                    let division_arg = find_option(&division_options.options, "division");
+
+                                    match (channel_arg, division_arg) {
+                                        (Some(ApplicationCommandInteractionDataOptionValue::Channel(partial_channel)),
+                                        Some(ApplicationCommandInteractionDataOptionValue::String(division))) => {
+                                            let division = division.trim().to_string();
+                                            let result = self.with_team(&partial_channel.id, |team| {
+                                                team.division = Some(division.clone());
+                                                format!("Team {} is now in division {}", team.role.name, division)
+                                            });
+
+                                            result.unwrap_or_else(|| "Failed to set division, could not find team".to_string())
+                                        },
+                                        _ => "Failed to set division, invalid channel or division".to_string(),
+                                    }
+                                }
+                            },
+                            _ => "Invalid team->division suboption".to_string(),
+                        }
+                    },
+                    "create-from-category" => {
+                        if !self.is_host(&ctx, &interaction).await {
+                            return Err(CommandError::PermissionDenied)
+                        } else {
+                            match (find_option(&suboption.options, "category"), interaction.guild_id) {
+                                (Some(ApplicationCommandInteractionDataOptionValue::Channel(category)), Some(guild_id)) => match guild_channels_and_roles(&ctx, guild_id).await {
+                                    Some((guild_channels, guild_roles)) => {
+                                        let mut channels: Vec<_> = guild_channels
+                                            .values()
+                                            .filter(|channel| channel.kind == ChannelType::Text && channel.category_id == Some(category.id))
+                                            .collect();
+                                        channels.sort_by_key(|channel| channel.position);
+
+                                        let mut created = Vec::new();
+                                        let mut skipped = Vec::new();
+                                        let mut teams = self.teams.lock().unwrap();
+                                        for channel in channels {
+                                            let role = guild_roles
+                                                .values()
+                                                .find(|role| normalize_team_name(&role.name) == normalize_team_name(&channel.name));
+
+                                            match role.map(|role| (role, teams.create_team(channel.id, role.clone()))) {
+                                                Some((_, Ok(()))) => created.push(channel.id.mention().to_string()),
+                                                Some((_, Err(CreateTeamError::ChannelTaken))) => skipped.push(format!("{} (already a team)", channel.id.mention())),
+                                                Some((role, Err(CreateTeamError::RoleTaken(_)))) => {
+                                                    skipped.push(format!("{} ({} already has a team)", channel.id.mention(), role.name))
+                                                },
+                                                None => skipped.push(format!("{} (no matching role)", channel.id.mention())),
+                                            }
+                                        }
+
+                                        let mut lines = vec![format!("Created {} teams: {}", created.len(), created.join(", "))];
+                                        if skipped.len() > 0 {
+                                            lines.push(format!("Skipped {}: {}", skipped.len(), skipped.join(", ")));
+                                        }
+                                        lines.join("\n")
+                                    },
+                                    None => "Failed to create teams, could not fetch the guild's channels and roles".to_string(),
+                                },
+                                _ => "Failed to create teams, invalid category or guild id".to_string(),
+                            }
+                        }
+                    },
+                    "suggest" => {
+                        if !self.is_host(&ctx, &interaction).await {
+                            return Err(CommandError::PermissionDenied)
+                        } else {
+                            match interaction.guild_id {
+                                Some(guild_id) => match guild_channels_and_roles(&ctx, guild_id).await {
+                                    Some((guild_channels, guild_roles)) => {
+                                        let teams = self.teams.lock().unwrap();
+                                        let mut channels: Vec<_> = guild_channels
+                                            .values()
+                                            .filter(|channel| channel.kind == ChannelType::Text && !teams.teams.contains_key(&channel.id))
+                                            .collect();
+                                        channels.sort_by_key(|channel| channel.position);
+
+                                        let mut suggestions = Vec::new();
+                                        for channel in channels {
+                                            let role = guild_roles
+                                                .values()
+                                                .find(|role| normalize_team_name(&role.name) == normalize_team_name(&channel.name));
+                                            match role {
+                                                Some(role) => suggestions.push(format!("☐ {} → {}: `/team create channel:#{} role:@{}`", channel.id.mention(), role.name, channel.name, role.name)),
+                                                None => suggestions.push(format!("☐ {} has no matching role", channel.id.mention())),
+                                            }
+                                        }
+
+                                        if suggestions.len() == 0 {
+                                            "Every text channel already has a team".to_string()
+                                        } else {
+                                            suggestions.join("\n")
+                                        }
+                                    },
+                                    None => "Failed to suggest teams, could not fetch the guild's channels and roles".to_string(),
+                                },
+                                None => "Failed to suggest teams, no guild for interaction".to_string(),
+                            }
+                        }
+                    },
+                    "icon" => {
+                        let icon_options = suboption.options.get(0).ok_or(CommandError::MissingOption("subcommand"))?;
+                        match icon_options.name.as_str() {
+                            "set" => {
+                                if !self.is_host(&ctx, &interaction).await {
+                                    return Err(CommandError::PermissionDenied)
+                                } else {
+                                    let channel_arg = icon_options.options.get(0).and_then(|option| option.resolved.as_ref());
+                                    let emoji_arg = icon_options.options.get(1).and_then(|option| option.resolved.as_ref());
+
+                                    match (channel_arg, emoji_arg) {
+                                        (Some(ApplicationCommandInteractionDataOptionValue::Channel(partial_channel)),
+                                        Some(ApplicationCommandInteractionDataOptionValue::String(emoji))) => {
+                                            let emoji = emoji.trim();
+                                            if !is_valid_emoji(emoji) {
+                                                format!("{} is not a valid emoji", emoji)
+                                            } else {
+                                                let result = self.with_team(&partial_channel.id, |team| {
+                                                    team.icon = Some(emoji.to_string());
+                                                    format!("Team icon is now {}", emoji)
+                                                });
+
+                                                result.unwrap_or_else(|| "Failed to set icon, could not find team".to_string())
+                                            }
+                                        },
+                                        _ => "Failed to set icon, invalid channel or emoji".to_string(),
+                                    }
+                                }
+                            },
+                            _ => "Invalid team->icon suboption".to_string(),
+                        }
+                    },
+                    "motto" => {
+                        let motto_options = suboption.options.get(0).ok_or(CommandError::MissingOption("subcommand"))?;
+                        if !self.is_host(&ctx, &interaction).await {
+                            return Err(CommandError::PermissionDenied)
+                        } else {
+                            match (motto_options.name.as_str(), find_option(&motto_options.options, "channel")) {
+                                ("set", Some(ApplicationCommandInteractionDataOptionValue::Channel(partial_channel))) => {
+                                    match find_option(&motto_options.options, "text") {
+                                        Some(ApplicationCommandInteractionDataOptionValue::String(text)) => match sanitize_motto(text) {
+                                            Ok(motto) => {
+                                                let result = self.with_team(&partial_channel.id, |team| {
+                                                    let content = format!("**{}**'s motto is now: {}", team.display_name(), motto);
+                                                    team.motto = Some(motto);
+                                                    content
+                                                });
+                                                result.unwrap_or_else(|| "Failed to set motto, could not find team".to_string())
+                                            },
+                                            Err(reason) => reason,
+                                        },
+                                        _ => "Failed to set motto, invalid text".to_string(),
+                                    }
+                                },
+                                ("clear", Some(ApplicationCommandInteractionDataOptionValue::Channel(partial_channel))) => {
+                                    let result = self.with_team(&partial_channel.id, |team| {
+                                        team.motto = None;
+                                        format!("Removed **{}**'s motto", team.display_name())
+                                    });
+                                    result.unwrap_or_else(|| "Failed to clear motto, could not find team".to_string())
+                                },
+                                ("set", _) | ("clear", _) => "Failed to update motto, invalid channel".to_string(),
+                                _ => "Invalid team->motto suboption".to_string(),
+                            }
+                        }
+                    },
+                    "join" => {
+                        let channel = match find_option(&suboption.options, "channel").ok_or(CommandError::MissingOption("channel"))? {
+                            ApplicationCommandInteractionDataOptionValue::Channel(channel) => Some(channel.id),
+                            _ => None,
+                        };
+                        let (team, current) = {
+                            let teams = self.teams.lock().unwrap();
+                            let roles = interaction.member.as_ref().map(|member| member.roles.as_slice()).unwrap_or(&[]);
+                            let team = channel.and_then(|channel| teams.teams.get(&channel)).map(|team| (team.role.id, team.display_name()));
+                            let current = teams.team_for_roles(roles).and_then(|channel| teams.teams.get(&channel)).map(|team| (team.role.id, team.display_name()));
+                            (team, current)
+                        };
+
+                        match (interaction.member.clone(), team, current) {
+                            (None, _, _) => "Teams can only be joined in a server".to_string(),
+                            (_, None, _) => "That channel has no team".to_string(),
+                            (_, Some((role_id, name)), Some((current, _))) if current == role_id => format!("You're already on **{}**", name),
+                            (_, _, Some((_, current))) => format!("You're already on **{}**, `/team leave` it first", current),
+                            (Some(mut member), Some((role_id, name)), None) => match member.add_role(&ctx.http, role_id).await {
+                                Ok(()) => format!("{} joined **{}**", member.user.id.mention(), name),
+                                Err(err) => membership_error(&err, "add you to", &name),
+                            },
+                        }
+                    },
+                    "leave" => {
+                        let current = interaction.member.as_ref().and_then(|member| {
+                            let teams = self.teams.lock().unwrap();
+                            teams.team_for_roles(&member.roles).and_then(|channel| teams.teams.get(&channel)).map(|team| (team.role.id, team.display_name()))
+                        });
+
+                        match (interaction.member.clone(), current) {
+                            (Some(mut member), Some((role_id, name))) => match member.remove_role(&ctx.http, role_id).await {
+                                Ok(()) => format!("{} left **{}**", member.user.id.mention(), name),
+                                Err(err) => membership_error(&err, "remove you from", &name),
+                            },
+                            (Some(_), None) => "You're not on a team".to_string(),
+                            (None, _) => "Teams can only be left in a server".to_string(),
+                        }
+                    },
+                    "members" => {
+                        let channel = match find_option(&suboption.options, "channel") {
+                            Some(ApplicationCommandInteractionDataOptionValue::Channel(channel)) => Some(channel.id),
+                            _ => interaction.channel_id,
+                        };
+                        let team = channel
+                            .and_then(|channel| self.teams.lock().unwrap().teams.get(&channel).map(|team| (team.role.id, team.role.guild_id, team.display_name())));
+
+                        match team {
+                            Some((role_id, guild_id, name)) => {
+                                // Membership is whoever holds the role, as far as the cache knows.
+                                let members = ctx.cache
+                                    .guild_field(guild_id, |guild| {
+                                        let mut members: Vec<String> = guild.members
+                                            .values()
+                                            .filter(|member| member.roles.contains(&role_id))
+                                            .map(|member| member.nick.clone().unwrap_or_else(|| member.user.name.clone()))
+                                            .collect();
+                                        members.sort_by_key(|name| name.to_lowercase());
+                                        members
+                                    })
+                                    .await;
+
+                                match members {
+                                    Some(members) if members.is_empty() => format!("**{}** has no members yet, join with `/team join`", name),
+                                    Some(members) => format!("**{}** ({}): {}", name, members.len(), members.join(", ")),
+                                    None => "Failed to list members, the server isn't cached yet".to_string(),
+                                }
+                            },
+                            None => "That channel has no team".to_string(),
+                        }
+                    },
+                    "list" => match interaction.guild_id {
+                        Some(guild_id) => {
+                            let page = match find_option(&suboption.options, "page") {
+                                Some(ApplicationCommandInteractionDataOptionValue::Integer(page)) if *page > 0 => *page as usize,
+                                _ => 1,
+                            };
+                            return Ok(Response::TeamList(self.teams.lock().unwrap().team_list_embed(guild_id, page)))
+                        },
+                        None => "Teams only exist in servers".to_string(),
+                    },
+                    "info" => {
+                        let channel = match find_option(&suboption.options, "channel") {
+                            Some(ApplicationCommandInteractionDataOptionValue::Channel(partial_channel)) => Some(partial_channel.id),
+                            _ => interaction.channel_id,
+                        };
+
+                        let teams = self.teams.lock().unwrap();
+                        match channel.and_then(|channel| teams.teams.get(&channel).map(|team| (channel, team))) {
+                            Some((channel, team)) => {
+                                let mut lines = vec![
+                                    format!("**{}** in {}", team.display_name(), channel.mention()),
+                                    format!("Score: {}", team.total_score()),
+                                ];
+                                if let Some(division) = &team.division {
+                                    lines.push(format!("Division: {}", division));
+                                }
+                                if let Some(motto) = &team.motto {
+                                    lines.push(format!("Motto: *{}*", motto));
+                                }
+                                lines.join("\n")
+                            },
+                            None => "No team for that channel".to_string(),
+                        }
+                    },
+                    _ => "Invalid team suboption".to_string(),
+                }
+            },
+            "host" => {
+                let suboption = data.options.get(0).ok_or(CommandError::MissingOption("subcommand"))?;
+                match (suboption.name.as_str(), interaction.guild_id) {
+                    ("set", Some(guild_id)) => {
+                        // Only administrators, a host could otherwise hand hosting to a role nobody else can remove.
+                        if !self.is_owner(&interaction) && !self.is_administrator(&interaction) {
+                            return Err(CommandError::PermissionDenied)
+                        } else {
+                            match find_option(&suboption.options, "role").ok_or(CommandError::MissingOption("role"))? {
+                                ApplicationCommandInteractionDataOptionValue::Role(role) => {
+                                    self.fix_host_role(guild_id, role.id);
+                                    format!("{} is now the host role", role.id.mention())
+                                },
+                                _ => "Failed to set host role, unknown role".to_string(),
+                            }
+                        }
+                    },
+                    ("show", Some(guild_id)) => match self.guild_host_role(guild_id) {
+                        Some(role_id) if self.host_role_fixed.lock().unwrap().contains(&guild_id) => format!("The host role is {}", role_id.mention()),
+                        Some(role_id) => format!("The host role is {}, found by its name, pick another one with `/host set`", role_id.mention()),
+                        None => CommandError::NoHostRole.to_string(),
+                    },
+                    (_, None) => "Host roles can only be used in a server".to_string(),
+                    _ => "Invalid host suboption".to_string(),
+                }
+            },
+            "config" => {
+                let suboption = data.options.get(0).ok_or(CommandError::MissingOption("subcommand"))?;
+                match suboption.name.as_str() {
+                    "ephemeral" => {
+                        if !self.is_host(&ctx, &interaction).await {
+                            return Err(CommandError::PermissionDenied)
+                        } else {
+                            let enabled_arg = suboption
+                                .options
+                                .get(0)
+                                .and_then(|option| option.resolved.as_ref());
+
+                            match (enabled_arg, interaction.guild_id) {
+                                (Some(ApplicationCommandInteractionDataOptionValue::Boolean(enabled)), Some(guild_id)) => {
+                                    self.update_config(guild_id, |config| config.ephemeral_admin = *enabled);
+                                    if *enabled {
+                                        "Administrative confirmations are now only shown to the invoker".to_string()
+                                    } else {
+                                        "Administrative confirmations are now shown publicly".to_string()
+                                    }
+                                },
+                                _ => "Failed to update config, invalid argument or guild id".to_string(),
+                            }
+                        }
+                    },
+                    "host-role-id" => {
+                        // Same rule as `/host set`, hosts can't hand hosting to another role.
+                        if !self.is_owner(&interaction) && !self.is_administrator(&interaction) {
+                            return Err(CommandError::PermissionDenied)
+                        } else {
+                            match (find_option(&suboption.options, "id"), interaction.guild_id) {
+                                (Some(ApplicationCommandInteractionDataOptionValue::String(id)), Some(guild_id)) => match id.trim().parse::<u64>() {
+                                    Ok(id) => match guild_id.roles(&ctx.http).await {
+                                        Ok(roles) => match roles.get(&RoleId(id)) {
+                                            Some(role) => {
+                                                self.fix_host_role(guild_id, role.id);
+                                                format!("{} is now the host role", role.name)
+                                            },
+                                            None => format!("No role with id {} in this guild", id),
+                                        },
+                                        Err(err) => format!("Failed to look up guild roles: {:?}", err),
+                                    },
+                                    Err(_) => format!("{} is not a valid role id", id),
+                                },
+                                _ => "Failed to update config, invalid argument or guild id".to_string(),
+                            }
+                        }
+                    },
+                    "export" => {
+                        if !self.is_owner(&interaction) && !self.is_host(&ctx, &interaction).await {
+                            return Err(CommandError::PermissionDenied)
+                        } else if let Some(guild_id) = interaction.guild_id {
+                            let config = self.configs.lock().unwrap().get(&guild_id).cloned().unwrap_or_default();
+                            let host_role = self.guild_host_role(guild_id);
+
+                            match guild_channels_and_roles(&ctx, guild_id).await {
+                                Some((channels, roles)) => {
+                                    let export = ConfigExport {
+                                        host_role: host_role
+                                            .and_then(|id| roles.get(&id))
+                                            .map(|role| (role.id, role.name.clone())),
+                                        reaction_channel_name: config.reaction_scoring
+                                            .and_then(|(channel_id, _)| channels.get(&channel_id))
+                                            .map(|channel| channel.name.clone()),
+                                        config,
+                                    };
+
+                                    // Kept on one line so it can be pasted straight into the `json` option of `/config import`.
+                                    match serde_json::to_string(&export) {
+                                        Ok(json) => format!("```json\n{}\n```", json),
+                                        Err(err) => format!("Failed to export config: {}", err),
+                                    }
+                                },
+                                None => "Failed to look up this guild's channels and roles".to_string(),
+                            }
+                        } else {
+                            "Failed to export config, no guild id".to_string()
+                        }
+                    },
+                    "import" => {
+                        if !self.is_owner(&interaction) && !self.is_host(&ctx, &interaction).await {
+                            return Err(CommandError::PermissionDenied)
+                        } else {
+                            match (find_option(&suboption.options, "json"), interaction.guild_id) {
+                                (Some(ApplicationCommandInteractionDataOptionValue::String(json)), Some(guild_id)) => {
+                                    // Tolerate the code block `/config export` wraps its output in.
+                                    let json = json.trim().trim_start_matches("```json").trim_matches('`').trim();
+
+                                    match serde_json::from_str::<ConfigExport>(json) {
+                                        Ok(ConfigExport { mut config, host_role, reaction_channel_name }) => match guild_channels_and_roles(&ctx, guild_id).await {
+                                            Some((channels, roles)) => {
+                                                let mut lines = vec!["Imported config".to_string()];
+
+                                                // Ids only match when importing back into the same guild, otherwise fall back to the name.
+                                                if let Some((channel_id, amount)) = config.reaction_scoring {
+                                                    let resolved = if channels.contains_key(&channel_id) {
+                                                        Some(channel_id)
+                                                    } else {
+                                                        reaction_channel_name
+                                                            .as_ref()
+                                                            .and_then(|name| channels.values().find(|channel| &channel.name == name))
+                                                            .map(|channel| channel.id)
+                                                    };
+
+                                                    match resolved {
+                                                        Some(resolved) => {
+                                                            config.reaction_scoring = Some((resolved, amount));
+                                                            if resolved != channel_id {
+                                                                lines.push(format!("Reaction scoring channel matched by name to {}", resolved.mention()));
+                                                            }
+                                                        },
+                                                        None => {
+                                                            config.reaction_scoring = None;
+                                                            lines.push(format!(
+                                                                "No channel named #{} here, reaction scoring is disabled",
+                                                                reaction_channel_name.as_deref().unwrap_or("?"),
+                                                            ));
+                                                        },
+                                                    }
+                                                }
+
+                                                if let Some((role_id, name)) = host_role {
+                                                    // Nothing has been applied yet, so refusing here leaves the config as it was.
+                                                    if !self.is_owner(&interaction) && !self.is_administrator(&interaction) {
+                                                        return Err(CommandError::PermissionDenied)
+                                                    }
+
+                                                    let resolved = roles
+                                                        .get(&role_id)
+                                                        .or_else(|| roles.values().find(|role| role.name == name));
+
+                                                    match resolved {
+                                                        Some(role) => {
+                                                            self.fix_host_role(guild_id, role.id);
+                                                            if role.id != role_id {
+                                                                lines.push(format!("Host role matched by name to {}", role.name));
+                                                            }
+                                                        },
+                                                        None => lines.push(format!("No role named {} here, the host role is unchanged", name)),
+                                                    }
+                                                }
+
+                                                self.update_config(guild_id, |current| *current = config);
+                                                lines.join("\n")
+                                            },
+                                            None => "Failed to look up this guild's channels and roles".to_string(),
+                                        },
+                                        Err(err) => format!("That isn't a valid config export: {}", err),
+                                    }
+                                },
+                                _ => "Failed to import config, invalid argument or guild id".to_string(),
+                            }
+                        }
+                    },
+                    "confirm-adjust" => {
+                        if !self.is_host(&ctx, &interaction).await {
+                            return Err(CommandError::PermissionDenied)
+                        } else {
+                            match (find_option(&suboption.options, "enabled"), interaction.guild_id) {
+                                (Some(ApplicationCommandInteractionDataOptionValue::Boolean(enabled)), Some(guild_id)) => {
+                                    self.update_config(guild_id, |config| config.confirm_adjust = *enabled);
+                                    if *enabled {
+                                        "Score adjustments now require the `team` option".to_string()
+                                    } else {
+                                        "Score adjustments no longer require the `team` option".to_string()
+                                    }
+                                },
+                                _ => "Failed to update config, invalid argument or guild id".to_string(),
+                            }
+                        }
+                    },
+                    "reaction-scoring" => {
+                        if !self.is_host(&ctx, &interaction).await {
+                            return Err(CommandError::PermissionDenied)
+                        } else {
+                            let channel_arg = find_option(&suboption.options, "channel");
+                            let amount_arg = find_option(&suboption.options, "amount");
+
+                            match (channel_arg, amount_arg, interaction.guild_id) {
+                                (Some(ApplicationCommandInteractionDataOptionValue::Channel(partial_channel)),
+                                Some(ApplicationCommandInteractionDataOptionValue::Integer(amount)),
+                                Some(guild_id)) => {
+                                    self.update_config(guild_id, |config| config.reaction_scoring = Some((partial_channel.id, *amount)));
+                                    format!("Hosts can now react with ➕/➖ in {} to adjust a team by {}", partial_channel.id.mention(), amount)
+                                },
+                                (None, _, Some(guild_id)) => {
+                                    self.update_config(guild_id, |config| config.reaction_scoring = None);
+                                    "Reaction scoring is now disabled".to_string()
+                                },
+                                _ => "Failed to update config, invalid argument or guild id".to_string(),
+                            }
+                        }
+                    },
+                    "score-step" => {
+                        if !self.is_host(&ctx, &interaction).await {
+                            return Err(CommandError::PermissionDenied)
+                        } else {
+                            let mode = match find_option(&suboption.options, "mode") {
+                                Some(ApplicationCommandInteractionDataOptionValue::String(mode)) if mode == "round" => StepMode::Round,
+                                _ => StepMode::Reject,
+                            };
+
+                            match (find_option(&suboption.options, "step"), interaction.guild_id) {
+                                (Some(ApplicationCommandInteractionDataOptionValue::Integer(step)), Some(guild_id)) if *step > 1 => {
+                                    self.update_config(guild_id, |config| config.score_step = Some((*step, mode)));
+                                    match mode {
+                                        StepMode::Reject => format!("Adjustments that aren't a multiple of {} are now rejected", step),
+                                        StepMode::Round => format!("Adjustments are now rounded to the nearest multiple of {}", step),
+                                    }
+                                },
+                                (_, Some(guild_id)) => {
+                                    self.update_config(guild_id, |config| config.score_step = None);
+                                    "Adjustments can now be any amount".to_string()
+                                },
+                                _ => "Failed to update config, invalid argument or guild id".to_string(),
+                            }
+                        }
+                    },
+                    _ => "Invalid config suboption".to_string(),
+                }
+            },
+            "score" => {
+                let suboption = data.options.get(0).ok_or(CommandError::MissingOption("subcommand"))?;
+                match interaction.guild_id {
+                    Some(guild_id) if self.guild_config(Some(guild_id)).mode == GameMode::Coop => {
+                        let target = self.guild_config(Some(guild_id)).coop_target;
+                        match suboption.name.as_str() {
+                            "show" => {
+                                let score = self.teams.lock().unwrap().coop_score(guild_id);
+                                match target {
+                                    Some(target) => format!("The server has {} of {} points", score, target),
+                                    None => format!("The server has {} points", score),
+                                }
+                            },
+                            "adjust" => {
+                                if !self.is_host(&ctx, &interaction).await {
+                                    return Err(CommandError::PermissionDenied)
+                                } else if !self.scoring_allowed(Some(guild_id)) {
+                                    return Err(CommandError::NoActiveGame)
+                                } else {
+                                    let amount = match find_option(&suboption.options, "amount") {
+                                        Some(ApplicationCommandInteractionDataOptionValue::Integer(amount)) => Some(apply_score_step(*amount, &self.guild_config(Some(guild_id)))),
+                                        _ => None,
+                                    };
+
+                                    match amount {
+                                        Some(Err(step)) => format!("Cannot adjust shared score, adjustments must be a multiple of {}", step),
+                                        Some(Ok((amount, rounded))) => {
+                                            let content = self.adjust_coop_score(guild_id, amount);
+                                            if rounded {
+                                                format!("{} (rounded to the score step)", content)
+                                            } else {
+                                                content
+                                            }
+                                        },
+                                        None => "Adjustment wrong type, could not adjust".to_string(),
+                                    }
+                                }
+                            },
+                            _ => "Invalid score suboption".to_string(),
+                        }
+                    },
+                    Some(_) => "Cooperative mode is off, use `/team score` or enable it with `/game config mode`".to_string(),
+                    None => "No guild for interaction".to_string(),
+                }
+            },
+            "player" => {
+                let suboption = data.options.get(0).ok_or(CommandError::MissingOption("subcommand"))?;
+                match (suboption.name.as_str(), find_option(&suboption.options, "user"), interaction.guild_id) {
+                    ("stats", Some(ApplicationCommandInteractionDataOptionValue::User(user, _member)), Some(guild_id)) => {
+                        let points = self.player_points.lock().unwrap().get(&(guild_id, user.id)).cloned();
+                        match points {
+                            Some(points) => format!("{}'s teams have earned {} points while they were a member", user.tag(), points),
+                            None => format!("{} hasn't earned any points with a team yet", user.tag()),
+                        }
+                    },
+                    ("stats", _, _) => "Please provide a valid user".to_string(),
+                    _ => "Invalid player suboption".to_string(),
+                }
+            },
+            "game" => {
+                let suboption = data.options.get(0).ok_or(CommandError::MissingOption("subcommand"))?;
+                match suboption.name.as_str() {
+                    "config" => {
+                        let config_options = suboption.options.get(0).ok_or(CommandError::MissingOption("subcommand"))?;
+                        if !self.is_host(&ctx, &interaction).await {
+                            return Err(CommandError::PermissionDenied)
+                        } else {
+                            match (config_options.name.as_str(), interaction.guild_id) {
+                                ("max-team-score", Some(guild_id)) => match find_option(&config_options.options, "value") {
+                                    Some(ApplicationCommandInteractionDataOptionValue::Integer(value)) => {
+                                        self.update_config(guild_id, |config| config.max_team_score = Some(*value));
+                                        format!("Team scores are now capped at {}", value)
+                                    },
+                                    _ => {
+                                        self.update_config(guild_id, |config| config.max_team_score = None);
+                                        "Team scores are now uncapped".to_string()
+                                    },
+                                },
+                                ("require-active", Some(guild_id)) => match find_option(&config_options.options, "enabled") {
+                                    Some(ApplicationCommandInteractionDataOptionValue::Boolean(enabled)) => {
+                                        self.update_config(guild_id, |config| config.require_active_game = *enabled);
+                                        if *enabled {
+                                            "Scoring now requires an active game, start one with `/game start`".to_string()
+                                        } else {
+                                            "Scoring no longer requires an active game".to_string()
+                                        }
+                                    },
+                                    _ => "Failed to update game config, invalid argument".to_string(),
+                                },
+                                ("percent-rounding", Some(guild_id)) => {
+                                    let rounding = match find_option(&config_options.options, "mode") {
+                                        Some(ApplicationCommandInteractionDataOptionValue::String(mode)) if mode == "down" => Rounding::Down,
+                                        Some(ApplicationCommandInteractionDataOptionValue::String(mode)) if mode == "up" => Rounding::Up,
+                                        _ => Rounding::Nearest,
+                                    };
+
+                                    self.update_config(guild_id, |config| config.percent_rounding = rounding);
+                                    format!("Percentage adjustments now round {}", match rounding {
+                                        Rounding::Nearest => "to the nearest point",
+                                        Rounding::Down => "down",
+                                        Rounding::Up => "up",
+                                    })
+                                },
+                                ("mode", Some(guild_id)) => {
+                                    let mode = match find_option(&config_options.options, "mode") {
+                                        Some(ApplicationCommandInteractionDataOptionValue::String(mode)) if mode == "coop" => GameMode::Coop,
+                                        _ => GameMode::Versus,
+                                    };
+                                    let target = match find_option(&config_options.options, "target") {
+                                        Some(ApplicationCommandInteractionDataOptionValue::Integer(target)) => Some(*target),
+                                        _ => None,
+                                    };
+
+                                    self.update_config(guild_id, |config| {
+                                        config.mode = mode;
+                                        config.coop_target = target;
+                                    });
+                                    match (mode, target) {
+                                        (GameMode::Coop, Some(target)) => format!("Cooperative mode enabled, the server is aiming for {} points", target),
+                                        (GameMode::Coop, None) => "Cooperative mode enabled".to_string(),
+                                        (GameMode::Versus, _) => "Versus mode enabled, teams are scored separately".to_string(),
+                                    }
+                                },
+                                (_, None) => "Failed to update game config, no guild for interaction".to_string(),
+                                _ => "Invalid game->config suboption".to_string(),
+                            }
+                        }
+                    },
+                    "start" => {
+                        if !self.is_host(&ctx, &interaction).await {
+                            return Err(CommandError::PermissionDenied)
+                        } else if let Some(guild_id) = interaction.guild_id {
+                            if self.active_games.lock().unwrap().insert(guild_id) {
+                                // Every game gets the whole question bank again.
+                                self.questions.lock().unwrap().reset(guild_id);
+                                "Game started, good luck!".to_string()
+                            } else {
+                                "A game is already running, finish it with `/game end`".to_string()
+                            }
+                        } else {
+                            "No guild for interaction".to_string()
+                        }
+                    },
+                    "end" => {
+                        if !self.is_host(&ctx, &interaction).await {
+                            return Err(CommandError::PermissionDenied)
+                        } else if let Some(guild_id) = interaction.guild_id {
+                            // Only a running game has a winner, ending twice mustn't count the win twice.
+                            if !self.active_games.lock().unwrap().remove(&guild_id) {
+                                return Ok("No game is running, start one with `/game start`".to_string().into())
+                            }
+
+                            let leaders: Vec<(Role, String, i64, Option<String>)> = {
+                                let teams = self.teams.lock().unwrap();
+                                let standings = teams.sorted_teams(guild_id);
+                                let top = standings.first().map(|(_, team)| team.total_score());
+                                standings
+                                    .into_iter()
+                                    .filter(|(_, team)| Some(team.total_score()) == top)
+                                    .map(|(_, team)| (team.role.clone(), team.display_name(), team.total_score(), team.motto.clone()))
+                                    .collect()
+                            };
+
+                            match leaders.as_slice() {
+                                [] => "Game over, but there are no teams to crown".to_string(),
+                                [(role, name, score, motto)] => {
+                                    let recorded = self.leaderboard.lock().unwrap().record_win(guild_id, role);
+                                    let announcement = match recorded {
+                                        Ok(wins) => format!("Game over! {} wins with {} points, that's {} all-time wins", name, score, wins),
+                                        Err(err) => format!("Game over! {} wins with {} points, but the win could not be saved: {}", name, score, err),
+                                    };
+                                    match motto {
+                                        Some(motto) => format!("{}\n> *{}*", announcement, motto),
+                                        None => announcement,
+                                    }
+                                },
+                                tied => {
+                                    let names: Vec<&str> = tied.iter().map(|(_, name, _, _)| name.as_str()).collect();
+                                    format!("Game over! {} tied with {} points, no win was recorded", names.join(", "), tied[0].2)
+                                },
+                            }
+                        } else {
+                            "No guild for interaction".to_string()
+                        }
+                    },
+                    _ => "Invalid game suboption".to_string(),
+                }
+            },
+            "scoreboard" => {
+                let suboption = data.options.get(0).ok_or(CommandError::MissingOption("subcommand"))?;
+                if !self.is_host(&ctx, &interaction).await {
+                    return Err(CommandError::PermissionDenied)
+                } else {
+                    match (suboption.name.as_str(), find_option(&suboption.options, "teams"), interaction.guild_id) {
+                        ("order", Some(ApplicationCommandInteractionDataOptionValue::String(order)), Some(guild_id)) if order.trim().eq_ignore_ascii_case("auto") => {
+                            self.teams.lock().unwrap().set_manual_order(guild_id, None);
+                            "The scoreboard is sorted by score again".to_string()
+                        },
+                        ("order", Some(ApplicationCommandInteractionDataOptionValue::String(order)), Some(guild_id)) => {
+                            let mut teams = self.teams.lock().unwrap();
+                            let mut channels = Vec::new();
+                            let mut unknown = Vec::new();
+                            for reference in order.split(|c: char| c.is_whitespace() || c == ',').filter(|reference| !reference.is_empty()) {
+                                match teams.resolve_team_ref(guild_id, reference) {
+                                    Some(channel_id) if !channels.contains(&channel_id) => channels.push(channel_id),
+                                    Some(_) => {},
+                                    None => unknown.push(format!("`{}`", reference)),
+                                }
+                            }
+
+                            if !unknown.is_empty() {
+                                format!("Scoreboard order unchanged, unknown teams: {}", unknown.join(", "))
+                            } else if channels.is_empty() {
+                                "Scoreboard order unchanged, no teams given".to_string()
+                            } else {
+                                let count = channels.len();
+                                teams.set_manual_order(guild_id, Some(channels));
+                                format!("The scoreboard now lists {} teams in the given order, use `auto` to sort by score again", count)
+                            }
+                        },
+                        (_, _, None) => "No guild for interaction".to_string(),
+                        _ => "Invalid scoreboard suboption".to_string(),
+                    }
+                }
+            },
+            "buzz" => {
+                let suboption = data.options.get(0).ok_or(CommandError::MissingOption("subcommand"))?;
+                match (suboption.name.as_str(), interaction.guild_id, interaction.channel_id) {
+                    ("in", Some(guild_id), Some(channel_id)) => {
+                        let team = interaction.member
+                            .as_ref()
+                            .and_then(|member| {
+                                let teams = self.teams.lock().unwrap();
+                                teams.team_for_roles(&member.roles).and_then(|team| teams.teams.get(&team)).map(|team| (team.role.id, team.display_name()))
+                            });
+
+                        match team {
+                            _ if !self.active_questions.lock().unwrap().get(&channel_id).map_or(false, |active| !active.expired()) => {
+                                "There's no question to buzz in on, wait for `/trivia ask`".to_string()
+                            },
+                            Some((role_id, name)) => {
+                                let mut buzzers = self.buzzers.lock().unwrap();
+                                match buzzers.get(&channel_id).copied() {
+                                    None => {
+                                        buzzers.insert(channel_id, (role_id, Instant::now()));
+                                        format!("**{}** buzzed in!", name)
+                                    },
+                                    Some((first, _)) if first == role_id => format!("**{}** already buzzed in", name),
+                                    Some((first, at)) => {
+                                        drop(buzzers);
+                                        let behind = Instant::now().saturating_duration_since(at);
+                                        format!("Too late, {} buzzed first ({:.1}s ahead)", self.buzzed_team_name(guild_id, first), behind.as_secs_f64())
+                                    },
+                                }
+                            },
+                            None => "You're not on a team, so there's nothing to buzz in for".to_string(),
+                        }
+                    },
+                    ("clear", Some(guild_id), Some(channel_id)) => {
+                        if !self.is_host(&ctx, &interaction).await {
+                            return Err(CommandError::PermissionDenied)
+                        } else {
+                            match self.buzzers.lock().unwrap().remove(&channel_id) {
+                                Some((role_id, _)) => format!("Cleared {}'s buzz, anyone can buzz in again", self.buzzed_team_name(guild_id, role_id)),
+                                None => "Nobody has buzzed in".to_string(),
+                            }
+                        }
+                    },
+                    (_, None, _) | (_, _, None) => "Buzzing in only works in a server channel".to_string(),
+                    _ => "Invalid buzz suboption".to_string(),
+                }
+            },
+            "trivia" => {
+                let suboption = data.options.get(0).ok_or(CommandError::MissingOption("subcommand"))?;
+                match (suboption.name.as_str(), interaction.guild_id) {
+                    ("ask", Some(guild_id)) => {
+                        if !self.is_host(&ctx, &interaction).await {
+                            return Err(CommandError::PermissionDenied)
+                        } else {
+                            match interaction.channel_id {
+                                Some(channel_id) => {
+                                    let config = self.guild_config(Some(guild_id));
+                                    let category = match find_option(&suboption.options, "category") {
+                                        Some(ApplicationCommandInteractionDataOptionValue::String(category)) if !category.trim().is_empty() => Some(category.trim()),
+                                        _ => None,
+                                    };
+                                    // Only used without a `category`, which already narrows it down to one.
+                                    let exclude: Vec<&str> = match find_option(&suboption.options, "exclude") {
+                                        Some(ApplicationCommandInteractionDataOptionValue::String(exclude)) => {
+                                            exclude.split(',').map(str::trim).filter(|category| !category.is_empty()).collect()
+                                        },
+                                        _ => Vec::new(),
+                                    };
+                                    let timer = match find_option(&suboption.options, "timer") {
+                                        Some(ApplicationCommandInteractionDataOptionValue::Integer(seconds)) => match u64::try_from(*seconds) {
+                                            Ok(seconds) if (5..=600).contains(&seconds) => Some(Duration::from_secs(seconds)),
+                                            _ => return Ok("The timer must be between 5 and 600 seconds".to_string().into()),
+                                        },
+                                        _ => None,
+                                    };
+                                    let mut questions = self.questions.lock().unwrap();
+                                    if questions.len() == 0 {
+                                        "The question bank is empty, add questions to the questions file".to_string()
+                                    } else {
+                                        // Counted from the last `/game start`, which is when the bank was last reset.
+                                        let number = questions.asked(guild_id) + 1;
+                                        let remaining = questions.remaining(guild_id).saturating_sub(1);
+                                        let drawn = questions.draw(guild_id, category, &exclude).map(|(index, question)| {
+                                            let mut details = Vec::new();
+                                            if !question.category.is_empty() {
+                                                details.push(question.category.clone());
+                                            }
+                                            details.push(format!("difficulty {} for {} points", question.difficulty, config.question_points(question.difficulty)));
+                                            details.push(format!("{} left", remaining));
+                                            let mut content = format!("**Question {}** ({})\n{}", number, details.join(", "), question.prompt);
+                                            if let Some(timer) = timer {
+                                                content.push_str(&format!("\n⏱ {} seconds to answer", timer.as_secs()));
+                                            }
+                                            (index, question.answer().to_string(), content)
+                                        });
+
+                                        match drawn {
+                                            Some((index, answer, content)) => {
+                                                let active = ActiveQuestion {
+                                                    index: index,
+                                                    deadline: timer.map(|timer| Instant::now() + timer),
+                                                };
+                                                // Asking again before anyone answers moves on, revealing the previous answer.
+                                                let previous = self.active_questions.lock().unwrap().insert(channel_id, active);
+                                                self.buzzers.lock().unwrap().remove(&channel_id);
+                                                if let Some(timer) = timer {
+                                                    self.spawn_question_timer(ctx, channel_id, active, timer, answer);
+                                                }
+                                                match previous.and_then(|previous| questions.get(previous.index)) {
+                                                    Some(previous) => format!("The answer was **{}**\n\n{}", previous.answer(), content),
+                                                    None => content,
+                                                }
+                                            },
+                                            None => match category {
+                                                Some(category) if questions.categories(guild_id).keys().any(|known| known.eq_ignore_ascii_case(category)) => {
+                                                    format!("Every {} question has been asked already, pick another category from `/trivia categories`", category)
+                                                },
+                                                Some(category) => format!("There is no {} category, see `/trivia categories` for the ones there are", category),
+                                                None if !exclude.is_empty() => "No questions match the current filters".to_string(),
+                                                None => "Every question has been asked already".to_string(),
+                                            },
+                                        }
+                                    }
+                                },
+                                None => "No channel for interaction".to_string(),
+                            }
+                        }
+                    },
+                    ("answer", Some(guild_id)) => {
+                        let text = match find_option(&suboption.options, "text") {
+                            Some(ApplicationCommandInteractionDataOptionValue::String(text)) => Some(text),
+                            _ => None,
+                        };
+                        // Once a team has buzzed in the question is theirs to answer, and theirs to score for.
+                        let buzzed = interaction.channel_id.and_then(|channel_id| self.buzzers.lock().unwrap().get(&channel_id).copied());
+                        if let Some((role_id, _)) = buzzed {
+                            let roles = interaction.member.as_ref().map(|member| member.roles.as_slice()).unwrap_or(&[]);
+                            if !roles.contains(&role_id) {
+                                return Ok(format!("Too late, {} buzzed first", self.buzzed_team_name(guild_id, role_id)).into());
+                            }
+                        }
+
+                        // The question is over once its answer has been revealed, right or wrong.
+                        let active = interaction.channel_id.and_then(|channel_id| {
+                            self.buzzers.lock().unwrap().remove(&channel_id);
+                            self.active_questions.lock().unwrap().remove(&channel_id).map(|active| (channel_id, active))
+                        });
+                        let expired = active.map_or(false, |(_, active)| active.expired());
+                        let question = active.and_then(|(channel_id, ActiveQuestion { index, .. })| {
+                            let scoring_channel = buzzed
+                                .and_then(|(role_id, _)| self.teams.lock().unwrap().team_exists_for_role(guild_id, role_id))
+                                .unwrap_or(channel_id);
+                            self.questions.lock().unwrap().get(index).map(|question| (scoring_channel, question.clone()))
+                        });
+
+                        match (text, question) {
+                            // The timer is about to reveal it, whoever removes the question first gets to.
+                            (Some(_), Some((_, question))) if expired => format!("Time's up, the answer was **{}**", question.answer()),
+                            (Some(text), Some((channel_id, question))) => {
+                                let (answer, difficulty) = (question.answer(), question.difficulty);
+                                if !question.is_correct(text) {
+                                    format!("Incorrect, the answer was **{}**", answer)
+                                } else if !self.scoring_allowed(Some(guild_id)) {
+                                    format!("Correct, the answer was **{}**! No points, there is no game running", answer)
+                                } else if self.is_coop(Some(guild_id)) {
+                                    let points = self.guild_config(Some(guild_id)).question_points(difficulty);
+                                    format!("Correct, the answer was **{}**! {}", answer, self.adjust_coop_score(guild_id, points))
+                                } else {
+                                    let config = self.guild_config(Some(guild_id));
+                                    let points = config.question_points(difficulty);
+                                    let scored = self.score_team(&channel_id, |team| {
+                                        let (score, clamped) = match add_to_score(team.score, points, &config) {
+                                            Some(adjusted) => adjusted,
+                                            None => return (format!("Cannot adjust **{}** by {:+}, score out of range", team.display_name(), points), None),
+                                        };
+                                        let applied = score - team.score;
+                                        team.score = score;
+                                        let content = format!("**{}** → {}", team.display_name(), team.total_score());
+                                        if clamped {
+                                            (format!("{} (capped at the maximum team score)", content), Some((team.role.id, applied)))
+                                        } else {
+                                            (content, Some((team.role.id, applied)))
+                                        }
+                                    }, |(_, applied)| applied.map_or(0, |(_, delta)| delta));
+
+                                    match scored {
+                                        Some((team, Some((role_id, applied)))) => {
+                                            self.credit_members(ctx, guild_id, &[(role_id, applied)]).await;
+                                            format!("Correct, the answer was **{}**! {:+} points, {}", answer, applied, team)
+                                        },
+                                        Some((content, None)) => format!("Correct, the answer was **{}**! {}", answer, content),
+                                        None => format!("Correct, the answer was **{}**! This channel has no team to score for", answer),
+                                    }
+                                }
+                            },
+                            (Some(_), None) => "There's no question in this channel, ask one with `/trivia ask`".to_string(),
+                            (None, _) => "Failed to answer, invalid text".to_string(),
+                        }
+                    },
+                    ("fetch", Some(_)) => {
+                        if !self.is_host(&ctx, &interaction).await {
+                            return Err(CommandError::PermissionDenied)
+                        } else {
+                            let amount = match find_option(&suboption.options, "amount").ok_or(CommandError::MissingOption("amount"))? {
+                                ApplicationCommandInteractionDataOptionValue::Integer(amount) => u8::try_from(*amount).ok().filter(|amount| (1..=50).contains(amount)),
+                                _ => None,
+                            };
+                            let category = match find_option(&suboption.options, "category") {
+                                Some(ApplicationCommandInteractionDataOptionValue::Integer(category)) => u32::try_from(*category).ok(),
+                                _ => None,
+                            };
+
+                            match amount {
+                                Some(amount) => match questions::fetch_opentdb(amount, category).await {
+                                    Ok(fetched) if fetched.is_empty() => "Open Trivia DB has no questions for that, try another category".to_string(),
+                                    Ok(fetched) => {
+                                        let count = fetched.len();
+                                        let mut questions = self.questions.lock().unwrap();
+                                        let added = questions.extend(fetched);
+                                        let source = match category.and_then(questions::opentdb_category_name) {
+                                            Some(name) => format!("{} questions from Open Trivia DB", name),
+                                            None => "questions from Open Trivia DB".to_string(),
+                                        };
+                                        if added < count {
+                                            format!("Added {} {}, skipping {} already in the bank, the bank now has {}", added, source, count - added, questions.len())
+                                        } else {
+                                            format!("Added {} {}, the bank now has {}", added, source, questions.len())
+                                        }
+                                    },
+                                    Err(err) => format!("Failed to fetch questions, {}", err),
+                                },
+                                None => "Amount must be between 1 and 50".to_string(),
+                            }
+                        }
+                    },
+                    ("categories", Some(guild_id)) => {
+                        let questions = self.questions.lock().unwrap();
+                        let categories = questions.categories(guild_id);
+                        if categories.is_empty() {
+                            "The question bank is empty, add questions to the questions file".to_string()
+                        } else {
+                            let lines: Vec<String> = categories
+                                .iter()
+                                .map(|(category, unused)| match *category {
+                                    "" => format!("**Uncategorized**: {} left", unused),
+                                    category => format!("**{}**: {} left", category, unused),
+                                })
+                                .collect();
+                            lines.join("\n")
+                        }
+                    },
+                    ("points", Some(guild_id)) => {
+                        if !self.is_host(&ctx, &interaction).await {
+                            return Err(CommandError::PermissionDenied)
+                        } else {
+                            let difficulty = match find_option(&suboption.options, "difficulty").ok_or(CommandError::MissingOption("difficulty"))? {
+                                ApplicationCommandInteractionDataOptionValue::Integer(difficulty) => u8::try_from(*difficulty).ok(),
+                                _ => None,
+                            };
+                            let points = match find_option(&suboption.options, "points") {
+                                Some(ApplicationCommandInteractionDataOptionValue::Integer(points)) => Some(*points),
+                                _ => None,
+                            };
+
+                            match difficulty {
+                                Some(difficulty) => {
+                                    self.update_config(guild_id, |config| match points {
+                                        Some(points) => {
+                                            config.difficulty_points.insert(difficulty, points);
+                                        },
+                                        None => {
+                                            config.difficulty_points.remove(&difficulty);
+                                        },
+                                    });
+                                    let points = self.guild_config(Some(guild_id)).question_points(difficulty);
+                                    format!("Difficulty {} questions are now worth {} points", difficulty, points)
+                                },
+                                None => "Difficulty must be between 0 and 255".to_string(),
+                            }
+                        }
+                    },
+                    ("leaderboard", Some(guild_id)) => {
+                        let standings = self.leaderboard.lock().unwrap().standings(guild_id);
+                        if standings.is_empty() {
+                            "No games have been won yet, finish one with `/game end`".to_string()
+                        } else {
+                            let teams = self.teams.lock().unwrap();
+                            standings
+                                .iter()
+                                .enumerate()
+                                .map(|(rank, (role_id, record))| {
+                                    // Show the team's current name when it still exists, it may have been renamed since its last win.
+                                    let name = teams
+                                        .team_exists_for_role(guild_id, RoleId(*role_id))
+                                        .and_then(|channel_id| teams.teams.get(&channel_id))
+                                        .map(|team| team.role.name.clone())
+                                        .unwrap_or_else(|| record.name.clone());
+                                    let plural = if record.wins == 1 { "win" } else { "wins" };
+                                    format!("{}. {}: {} {}", rank + 1, name, record.wins, plural)
+                                })
+                                .collect::<Vec<_>>()
+                                .join("\n")
+                        }
+                    },
+                    (_, None) => "No guild for interaction".to_string(),
+                    _ => "Invalid trivia suboption".to_string(),
+                }
+            },
+            "admin" => {
+                let suboption = data.options.get(0).ok_or(CommandError::MissingOption("subcommand"))?;
+                if !self.is_owner(&interaction) {
+                    return Err(CommandError::PermissionDenied)
+                } else {
+                    match suboption.name.as_str() {
+                        "reregister" => {
+                            let global = match find_option(&suboption.options, "global") {
+                                Some(ApplicationCommandInteractionDataOptionValue::Boolean(global)) => *global,
+                                _ => false,
+                            };
+
+                            match (global, interaction.guild_id) {
+                                (true, _) => match ApplicationCommand::set_global_application_commands(&ctx.http, register_commands).await {
+                                    Ok(commands) => format!("Registered {} global commands", commands.len()),
+                                    Err(err) => format!("Failed to register global commands: {:?}", err),
+                                },
+                                (false, Some(guild_id)) => match guild_id.set_application_commands(&ctx.http, register_commands).await {
+                                    Ok(commands) => format!("Registered {} commands for this guild", commands.len()),
+                                    Err(err) => format!("Failed to register guild commands: {:?}", err),
+                                },
+                                (false, None) => "No guild to register commands for, use global instead".to_string(),
+                            }
+                        },
+                        "guilds" => {
+                            let page = match find_option(&suboption.options, "page") {
+                                Some(ApplicationCommandInteractionDataOptionValue::Integer(page)) if *page > 0 => *page as usize,
+                                _ => 1,
+                            };
+                            self.list_guilds(&ctx, page).await
+                        },
+                        "diag" => match interaction.guild_id {
+                            Some(guild_id) => {
+                                let teams = self.teams.lock().unwrap();
+                                let count = teams.teams.values().filter(|team| team.role.guild_id == guild_id).count();
+                                match teams.reconcile(guild_id) {
+                                    Ok(()) => format!("{} teams, scores match the scoring audit", count),
+                                    Err(discrepancy) => format!(
+                                        "{} teams, **scores drifted by {:+}**: scoring accounts for {:+} but the scores moved by {:+} since the audit started",
+                                        count,
+                                        discrepancy.actual - discrepancy.expected,
+                                        discrepancy.expected,
+                                        discrepancy.actual,
+                                    ),
+                                }
+                            },
+                            None => "No guild for interaction".to_string(),
+                        },
+                        "shutdown" => "Shutting down".to_string(),
+                        "debug-interaction" => {
+                            let last = interaction.guild_id.and_then(|guild_id| self.last_interaction.lock().unwrap().get(&guild_id).cloned());
+                            match last {
+                                Some(description) => format!("```\n{}\n```", description),
+                                None => "No commands have been received in this guild yet".to_string(),
+                            }
+                        },
+                        _ => "Invalid admin suboption".to_string(),
+                    }
+                }
+            },
+            _ => "Invalid command".to_string(),
+        };
+
+        Ok(Response::Text(content))
+    }
+
+    async fn list_guilds(&self, ctx: &Context, page: usize) -> String {
+        let mut guilds: Vec<(GuildId, String)> = Vec::new();
+        for guild_id in ctx.cache.guilds().await {
+            match ctx.cache.guild(guild_id).await {
+                Some(guild) => guilds.push((guild_id, guild.name)),
+                None => guilds.push((guild_id, "(unavailable)".to_string())),
+            }
+        }
+
+        // Without the cache (or right after connecting) Discord can still tell us which guilds we're in.
+        if guilds.is_empty() {
+            match ctx.http.get_guilds(&GuildPagination::After(GuildId(0)), 200).await {
+                Ok(infos) => guilds.extend(infos.into_iter().map(|info| (info.id, info.name))),
+                Err(why) => return format!("Failed to list guilds: {}", why),
+            }
+        }
+
+        if guilds.is_empty() {
+            return "Not connected to any guilds".to_string();
+        }
+
+        guilds.sort_by(|(_, a), (_, b)| a.to_lowercase().cmp(&b.to_lowercase()));
+        let host_roles = self.host_role.lock().unwrap().clone();
+
+        let mut team_counts: HashMap<GuildId, usize> = HashMap::new();
+        for team in self.teams.lock().unwrap().teams.values() {
+            *team_counts.entry(team.role.guild_id).or_default() += 1;
+        }
+
+        let pages = (guilds.len() + GUILDS_PER_PAGE - 1) / GUILDS_PER_PAGE;
+        let page = page.min(pages);
+        let mut lines = vec![format!("Connected to {} guilds (page {}/{})", guilds.len(), page, pages)];
+        for (guild_id, name) in guilds.iter().skip((page - 1) * GUILDS_PER_PAGE).take(GUILDS_PER_PAGE) {
+            let host = if host_roles.contains_key(guild_id) { "host role set" } else { "no host role" };
+            let teams = team_counts.get(guild_id).copied().unwrap_or(0);
+            lines.push(format!("{} (`{}`): {} teams, {}", name, guild_id, teams, host));
+        }
+        lines.join("\n")
+    }
+
+    async fn shutdown(&self, ctx: &Context, interaction: &Interaction) {
+        let user = interaction.member.as_ref().map(|member| &member.user).or(interaction.user.as_ref());
+        info!(user_id = ?user.map(|user| user.id), user = ?user.map(User::tag), "Shutdown requested");
+
+        let data = ctx.data.read().await;
+        match data.get::<ShardManagerContainer>() {
+            Some(manager) => manager.lock().await.shutdown_all().await,
+            None => error!("Cannot shut down, no shard manager registered"),
+        }
+    }
+
+    // Applies `amount`/`team` along with the optional `amount2`/`team2` and `amount3`/`team3` pairs.
+    // Returns the response along with the role and delta of each applied adjustment.
+    fn adjust_scores(&self, guild_id: Option<GuildId>, channel_id: Option<ChannelId>, options: &[ApplicationCommandInteractionDataOption]) -> (String, Vec<(RoleId, i64)>) {
+        let config = self.guild_config(guild_id);
+
+        let mut adjustments = Vec::new();
+        let mut errors = Vec::new();
+        for (index, (amount_name, team_name)) in [("amount", "team"), ("amount2", "team2"), ("amount3", "team3")].iter().enumerate() {
+            let amount = match find_option(options, amount_name) {
+                Some(ApplicationCommandInteractionDataOptionValue::Integer(amount)) => Some(*amount),
+                _ => None,
+            };
+            let team = match find_option(options, team_name) {
+                Some(ApplicationCommandInteractionDataOptionValue::Channel(partial_channel)) => Some(partial_channel.id),
+                _ => None,
+            };
+
+            match (amount, team) {
+                (Some(amount), Some(team)) => adjustments.push((amount, team)),
+                (Some(_), None) if index == 0 && config.confirm_adjust => {
+                    errors.push("Pass the `team` option to choose which team to adjust, this server requires naming it".to_string())
+                },
+                // Only the first adjustment falls back to the team of the current channel.
+                (Some(amount), None) if index == 0 => match channel_id {
+                    Some(channel_id) => adjustments.push((amount, channel_id)),
+                    None => errors.push("Missing team, could not adjust".to_string()),
+                },
+                (Some(_), None) => errors.push(format!("`{}` needs a matching `{}`", amount_name, team_name)),
+                (None, Some(_)) => errors.push(format!("`{}` needs a matching `{}`", team_name, amount_name)),
+                (None, None) => {},
+            }
+        }
+
+        if errors.len() > 0 {
+            return (errors.join("\n"), Vec::new());
+        }
+
+        let mut results = Vec::new();
+        let mut applied = Vec::new();
+        for (adjust, channel_id) in adjustments {
+            let (adjust, stepped) = match apply_score_step(adjust, &config) {
+                Ok(adjusted) => adjusted,
+                Err(step) => {
+                    results.push(format!("Cannot adjust by {:+}, adjustments must be a multiple of {}", adjust, step));
+                    continue;
+                },
+            };
+
+            let result = self.score_team(&channel_id, |team| {
+                let (score, clamped) = match add_to_score(team.score, adjust, &config) {
+                    Some(adjusted) => adjusted,
+                    None => return (format!("Cannot adjust **{}** by {:+}, score out of range", team.display_name(), adjust), None),
+                };
+
+                let delta = score.saturating_sub(team.score);
+                team.score = score;
+                let mut content = format!("Adjusted **{}** ({}) by {:+} → {}", team.display_name(), channel_id.mention(), adjust, team.score);
+                if stepped {
+                    content.push_str(" (rounded to the score step)");
+                }
+                if clamped {
+                    (format!("{} (capped at the maximum team score)", content), Some((team.role.id, delta)))
+                } else {
+                    (content, Some((team.role.id, delta)))
+                }
+            }, |(_, applied)| applied.map_or(0, |(_, delta)| delta));
+
+            results.push(match result {
+                Some((content, adjustment)) => {
+                    applied.extend(adjustment);
+                    content
+                },
+                None => "Missing team, could not adjust".to_string(),
+            });
+        }
+
+        if results.len() == 0 {
+            ("Adjustment wrong type, could not adjust".to_string(), applied)
+        } else {
+            (results.join("\n"), applied)
+        }
+    }
+
+    fn adjust_score_percent(&self, guild_id: Option<GuildId>, channel_id: ChannelId, percent: i64) -> (String, Vec<(RoleId, i64)>) {
+        let config = self.guild_config(guild_id);
+
+        let result = self.score_team(&channel_id, |team| {
+            let delta = match percent_of(team.score, percent, config.percent_rounding) {
+                Some(delta) => delta,
+                None => return (format!("Cannot adjust **{}** by {:+}%, score out of range", team.display_name(), percent), Vec::new()),
+            };
+            let delta = match apply_score_step(delta, &config) {
+                Ok((delta, _)) => delta,
+                Err(step) => {
+                    return (format!("Cannot adjust **{}** by {:+}% ({:+}), adjustments must be a multiple of {}", team.display_name(), percent, delta, step), Vec::new())
+                },
+            };
+            let (score, clamped) = match add_to_score(team.score, delta, &config) {
+                Some(adjusted) => adjusted,
+                None => return (format!("Cannot adjust **{}** by {:+}%, score out of range", team.display_name(), percent), Vec::new()),
+            };
+
+            let applied = score.saturating_sub(team.score);
+            team.score = score;
+            let content = format!("Adjusted **{}** ({}) by {:+}% ({:+}) → {}", team.display_name(), channel_id.mention(), percent, delta, team.score);
+            if clamped {
+                (format!("{} (capped at the maximum team score)", content), vec![(team.role.id, applied)])
+            } else {
+                (content, vec![(team.role.id, applied)])
+            }
+        }, |(_, applied)| applied.iter().map(|(_, delta)| delta).sum());
+
+        result.unwrap_or_else(|| ("Missing team, could not adjust".to_string(), Vec::new()))
+    }
+
+    // Credits every cached member holding a team's role with the points that team just earned.
+    async fn credit_members(&self, ctx: &Context, guild_id: GuildId, applied: &[(RoleId, i64)]) {
+        let guild = match ctx.cache.guild(guild_id).await {
+            Some(guild) => guild,
+            None => return,
+        };
+
+        let mut player_points = self.player_points.lock().unwrap();
+        for (role_id, delta) in applied {
+            for member in guild.members.values().filter(|member| member.roles.contains(role_id)) {
+                let points = player_points.entry((guild_id, member.user.id)).or_insert(0);
+                *points = points.saturating_add(*delta);
+            }
+        }
+    }
+
+    fn create_team(&self, channel: ChannelId, role: Role) -> Result<(), CreateTeamError> {
+        let mut teams_data = self.teams.lock().unwrap();
+        teams_data.create_team(channel, role)
+    }
+
+    // Reveals the answer to `active` once `timer` runs out, unless it has been answered or replaced by then.
+    fn spawn_question_timer(&self, ctx: &Context, channel_id: ChannelId, active: ActiveQuestion, timer: Duration, answer: String) {
+        let http = ctx.http.clone();
+        let active_questions = self.active_questions.clone();
+        let buzzers = self.buzzers.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(timer).await;
+
+            // Checked and cleared under one lock, so an answer that comes in at the same time can't also reveal it.
+            let expired = {
+                let mut active_questions = active_questions.lock().unwrap();
+                match active_questions.get(&channel_id) {
+                    Some(current) if *current == active => active_questions.remove(&channel_id).is_some(),
+                    _ => false,
+                }
+            };
+            if !expired {
+                return
+            }
+
+            buzzers.lock().unwrap().remove(&channel_id);
+            let content = format!("⏱ Time's up, the answer was **{}**", answer);
+            if let Err(why) = channel_id.send_message(&http, |message| message.content(content).allowed_mentions(no_pings)).await {
+                warn!(%channel_id, error = %why, "Cannot reveal timed out answer");
+            }
+        });
+    }
+
+    // The team holding a buzzer, falling back to mentioning its role when the team has since been removed.
+    fn buzzed_team_name(&self, guild_id: GuildId, role_id: RoleId) -> String {
+        let teams = self.teams.lock().unwrap();
+        match teams.team_exists_for_role(guild_id, role_id).and_then(|channel_id| teams.teams.get(&channel_id)) {
+            Some(team) => format!("**{}**", team.display_name()),
+            None => role_id.mention().to_string(),
+        }
+    }
+
+    fn require_host_role(&self, guild_id: Option<GuildId>) -> Result<RoleId, CommandError> {
+        guild_id.and_then(|guild_id| self.guild_host_role(guild_id)).ok_or(CommandError::NoHostRole)
+    }
+
+    fn save_teams(&self) {
+        if let Err(why) = self.teams.lock().unwrap().save() {
+            error!(error = %why, "Cannot save teams");
+        }
+    }
+
+    fn with_team<R>(&self, channel: &ChannelId, f: impl FnOnce(&mut Team) -> R) -> Option<R> {
+        let mut teams_data = self.teams.lock().unwrap();
+        teams_data.touch();
+        teams_data.teams.get_mut(channel).map(f)
+    }
+
+    // `with_team` for scoring, `delta` reads the change `f` reports having made so it's entered into the guild's
+    // audit under the same lock.
+    fn score_team<R>(&self, channel: &ChannelId, f: impl FnOnce(&mut Team) -> R, delta: impl FnOnce(&R) -> i64) -> Option<R> {
+        let mut teams_data = self.teams.lock().unwrap();
+        teams_data.touch();
+        let team = teams_data.teams.get_mut(channel)?;
+        let guild_id = team.role.guild_id;
+        let result = f(team);
+        teams_data.record_delta(guild_id, delta(&result));
+        Some(result)
+    }
+}
+
+// Channels and roles of a guild, from the cache when it has the guild and over HTTP otherwise, since
+// the cache can still be filling in right after startup or only partially cover large guilds.
+async fn guild_channels_and_roles(ctx: &Context, guild_id: GuildId) -> Option<(HashMap<ChannelId, GuildChannel>, HashMap<RoleId, Role>)> {
+    if let Some(guild) = ctx.cache.guild(guild_id).await {
+        return Some((guild.channels, guild.roles));
+    }
+
+    match (guild_id.channels(&ctx.http).await, guild_id.roles(&ctx.http).await) {
+        (Ok(channels), Ok(roles)) => Some((channels, roles)),
+        (Err(why), _) | (_, Err(why)) => {
+            warn!(%guild_id, error = %why, "Cannot fetch channels and roles");
+            None
+        },
+    }
+}
+
+// Follows up on a response with a preview of `colour`. Interaction responses can't carry files, so it's a followup, and
+// ephemeral so it never reaches the channel when the response itself didn't.
+async fn send_swatch(ctx: &Context, interaction: &Interaction, colour: Colour) {
+    let file = AttachmentType::Bytes {
+        data: Cow::from(color::color_swatch_png(colour, 64)),
+        filename: format!("{}.png", colour.hex()),
+    };
+
+    let result = interaction
+        .create_followup_message(&ctx.http, |message| {
+            message.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+            message.add_file(file)
+        })
+        .await;
+    if let Err(why) = result {
+        warn!(guild_id = ?interaction.guild_id, channel_id = ?interaction.channel_id, error = %why, "Cannot send color swatch");
+    }
+}
+
+fn is_missing_permissions(err: &SerenityError) -> bool {
+    match err {
+        SerenityError::Http(http_err) => match &**http_err {
+            // 50001 is "Missing Access" and 50013 is "Missing Permissions".
+            HttpError::UnsuccessfulRequest(response) => response.error.code == 50001 || response.error.code == 50013,
+            _ => false,
+        },
+        SerenityError::Model(ModelError::InvalidPermissions(_)) => true,
+        _ => false,
+    }
+}
+
+// Team names are user controlled, so anything that can contain one must not be able to ping `@everyone`, roles or users.
+fn no_pings(mentions: &mut CreateAllowedMentions) -> &mut CreateAllowedMentions {
+    mentions.empty_parse()
+}
+
+async fn send_response(ctx: &Context, interaction: &Interaction, response: &Response, ephemeral: bool) -> Result<(), SerenityError> {
+    interaction
+        .create_interaction_response(&ctx.http, |reply| {
+            reply
+                .kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|message| {
+                    if ephemeral {
+                        message.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+                    }
+                    match response {
+                        Response::Text(content) | Response::Swatch(content, _) => message.content(content),
+                        Response::Scoreboard(scoreboard) => message.add_embed(scoreboard_embed(scoreboard)),
+                        Response::TeamList(list) => message.add_embed(team_list_embed(list)),
+                    };
+                    message.allowed_mentions(no_pings)
+                })
+        })
+        .await
+}
+
+// Acknowledges an interaction with a "thinking" message, so commands that wait on role edits aren't given up on by
+// Discord while they do.
+async fn defer_response(ctx: &Context, interaction: &Interaction, ephemeral: bool) -> Result<(), SerenityError> {
+    interaction
+        .create_interaction_response(&ctx.http, |reply| {
+            reply
+                .kind(InteractionResponseType::DeferredChannelMessageWithSource)
+                .interaction_response_data(|message| {
+                    if ephemeral {
+                        message.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+                    }
+                    message
+                })
+        })
+        .await
+}
+
+// Sends `response` in whichever way fits how the interaction has been acknowledged so far. An ephemeral response can't
+// replace a public "thinking" message, so that one is deleted and the response follows up on its own instead.
+async fn deliver(ctx: &Context, interaction: &Interaction, response: &Response, ephemeral: bool, deferred: Option<bool>) -> Result<(), SerenityError> {
+    match deferred {
+        None => try_respond(ctx, interaction, response, ephemeral).await,
+        Some(false) if ephemeral => {
+            interaction.delete_original_interaction_response(&ctx.http).await?;
+            interaction
+                .create_followup_message(&ctx.http, |message| {
+                    message.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+                    match response {
+                        Response::Text(content) | Response::Swatch(content, _) => message.content(content),
+                        Response::Scoreboard(scoreboard) => message.embed(|embed| {
+                            *embed = scoreboard_embed(scoreboard);
+                            embed
+                        }),
+                        Response::TeamList(list) => message.embed(|embed| {
+                            *embed = team_list_embed(list);
+                            embed
+                        }),
+                    };
+                    message.allowed_mentions(no_pings)
+                })
+                .await
+                .map(|_| ())
+        },
+        Some(_) => edit_response(ctx, interaction, response).await,
+    }
+}
+
+// Replaces the deferred "thinking" message with the actual response, it stays ephemeral if it was deferred as such.
+async fn edit_response(ctx: &Context, interaction: &Interaction, response: &Response) -> Result<(), SerenityError> {
+    interaction
+        .edit_original_interaction_response(&ctx.http, |message| {
+            match response {
+                Response::Text(content) | Response::Swatch(content, _) => message.content(content),
+                Response::Scoreboard(scoreboard) => message.add_embed(scoreboard_embed(scoreboard)),
+                Response::TeamList(list) => message.add_embed(team_list_embed(list)),
+            };
+            message.allowed_mentions(no_pings)
+        })
+        .await
+        .map(|_| ())
+}
+
+// Embeds can hold at most 25 fields.
+const EMBED_FIELD_LIMIT: usize = 25;
+
+fn scoreboard_embed(scoreboard: &ScoreboardEmbed) -> CreateEmbed {
+    let mut embed = CreateEmbed::default();
+    embed.title("Scoreboard");
+
+    if scoreboard.rows.is_empty() {
+        embed.description("No teams yet, create one with `/team create`");
+        return embed;
+    }
+
+    // The leader's color, unless their role has none.
+    if let Some(leader) = scoreboard.rows.iter().find(|row| row.rank == 1).filter(|leader| leader.colour.0 != 0) {
+        embed.colour(leader.colour);
+    }
+
+    for row in scoreboard.rows.iter().take(EMBED_FIELD_LIMIT) {
+        let name = if row.highlighted { format!("{}. {} ◀", row.rank, row.name) } else { format!("{}. {}", row.rank, row.name) };
+        // Role mentions are shown in the role's color and never ping from inside an embed.
+        let value = match row.bonus {
+            0 => format!("<@&{}> **{}**", row.role_id, row.score),
+            bonus => format!("<@&{}> **{}** ({:+} bonus)", row.role_id, row.score, bonus),
+        };
+        embed.field(name, value, true);
+    }
+
+    let hidden = scoreboard.rows.len().saturating_sub(EMBED_FIELD_LIMIT);
+    let footer = if hidden > 0 {
+        format!("{} teams · {} points total · {} more in `/team score list compact:true`", scoreboard.rows.len(), scoreboard.total, hidden)
+    } else {
+        format!("{} teams · {} points total", scoreboard.rows.len(), scoreboard.total)
+    };
+    embed.footer(|f| f.text(footer));
+    embed
+}
+
+// Cuts `content` down to at most `limit` characters, at a line break where there is one, noting how much was left out.
+fn truncate_message(content: &str, limit: usize) -> String {
+    let total = content.chars().count();
+    if total <= limit {
+        return content.to_string();
+    }
+
+    // Leaves room for the note, which is never longer than this.
+    let kept: String = content.chars().take(limit.saturating_sub(48)).collect();
+    let kept = match kept.rfind('\n') {
+        Some(end) if end > 0 => &kept[..end],
+        _ => kept.as_str(),
+    };
+    format!("{}\n… {} more characters left out", kept, total - kept.chars().count())
+}
+
+// Explains why a team role couldn't be handed out or taken away, which is nearly always the bot's own permissions.
+fn membership_error(err: &SerenityError, action: &str, team: &str) -> String {
+    match err {
+        SerenityError::Model(ModelError::Hierarchy) => {
+            format!("Failed to {} **{}**, my highest role has to be above the team's role", action, team)
+        },
+        _ if is_missing_permissions(err) => format!("Failed to {} **{}**, I'm missing the Manage Roles permission", action, team),
+        _ => format!("Failed to {} **{}**: {}", action, team, err),
+    }
+}
+
+fn team_list_embed(list: &TeamListEmbed) -> CreateEmbed {
+    let mut embed = CreateEmbed::default();
+    embed.title("Teams");
+
+    if list.rows.is_empty() {
+        embed.description("No teams yet, create one with `/team create`");
+        return embed;
+    }
+
+    for row in &list.rows {
+        let colour = if row.colour.0 == 0 { "no color".to_string() } else { format!("`#{}`", row.colour.hex()) };
+        embed.field(&row.name, format!("{} · {} · {} points", row.channel_id.mention(), colour, row.score), true);
+    }
+
+    let footer = if list.pages > 1 {
+        format!("{} teams · page {}/{}, pick another with `page`", list.total, list.page, list.pages)
+    } else {
+        format!("{} teams", list.total)
+    };
+    embed.footer(|f| f.text(footer));
+    embed
+}
+
+fn interaction_user_id(interaction: &Interaction) -> Option<UserId> {
+    interaction.member.as_ref().map(|member| member.user.id).or(interaction.user.as_ref().map(|user| user.id))
+}
+
+// Ephemeral responses don't need send permissions in the channel, so fall back to one when a public response is refused.
+async fn try_respond(ctx: &Context, interaction: &Interaction, response: &Response, ephemeral: bool) -> Result<(), SerenityError> {
+    match send_response(ctx, interaction, response, ephemeral).await {
+        Err(err) if !ephemeral && is_missing_permissions(&err) => {
+            warn!(guild_id = ?interaction.guild_id, channel_id = ?interaction.channel_id, "Missing permissions to respond publicly, falling back to ephemeral");
+            send_response(ctx, interaction, response, true).await
+        },
+        result => result,
+    }
+}
+
+// Same fallback as `try_respond`, returning whether the deferred message ended up ephemeral.
+async fn try_defer(ctx: &Context, interaction: &Interaction, ephemeral: bool) -> Result<bool, SerenityError> {
+    match defer_response(ctx, interaction, ephemeral).await {
+        Err(err) if !ephemeral && is_missing_permissions(&err) => {
+            warn!(guild_id = ?interaction.guild_id, channel_id = ?interaction.channel_id, "Missing permissions to respond publicly, falling back to ephemeral");
+            defer_response(ctx, interaction, true).await.map(|_| true)
+        },
+        result => result.map(|_| ephemeral),
+    }
+}
+
+fn command_category(data: &ApplicationCommandInteractionData) -> CommandCategory {
+    let suboption = data.options.get(0);
+    match (data.name.as_str(), suboption.map(|option| option.name.as_str())) {
+        ("team", Some("create"))
+        | ("team", Some("rename"))
+        | ("team", Some("recolor"))
+        | ("team", Some("recolor-revert"))
+        | ("team", Some("copy-color"))
+        | ("team", Some("icon"))
+        | ("team", Some("suggest"))
+        | ("team", Some("create-from-category"))
+        | ("team", Some("division"))
+        | ("team", Some("motto"))
+        | ("team", Some("archive"))
+        | ("team", Some("unarchive")) => CommandCategory::Admin,
+        ("team", Some("score")) => {
+            // JSON scoreboards are for whoever is wiring up tooling, not the players.
+            let score_option = suboption.and_then(|option| option.options.get(0));
+            if score_option.map(|option| option.name.as_str()) == Some("watch") {
+                return CommandCategory::Private;
+            }
+
+            let list = score_option.filter(|option| option.name == "list");
+            match list.and_then(|list| find_option(&list.options, "format")) {
+                Some(ApplicationCommandInteractionDataOptionValue::String(format)) if format == "json" => CommandCategory::Admin,
+                _ => CommandCategory::Game,
+            }
+        },
+        // Other guilds' names are nobody else's business.
+        ("admin", Some("debug-interaction")) | ("admin", Some("guilds")) => CommandCategory::Private,
+        ("scoreboard", _) => CommandCategory::Admin,
+        ("config", _) | ("admin", _) | ("host", _) | ("game", Some("config")) | ("trivia", Some("points")) | ("trivia", Some("fetch")) => CommandCategory::Admin,
+        _ => CommandCategory::Game,
+    }
+}
+
+// Whether the commands Discord already has are the ones `register_commands` describes, so startup can skip
+// re-registering them. Commands can come back in any order, options and choices keep the order they were given in.
+fn commands_match(existing: &[ApplicationCommand], desired: &[Value]) -> bool {
+    existing.len() == desired.len() && desired.iter().all(|command| {
+        existing.iter().any(|existing| {
+            command["name"].as_str() == Some(existing.name.as_str())
+                && command["description"].as_str() == Some(existing.description.as_str())
+                && options_match(&existing.options, command.get("options"))
+        })
+    })
+}
+
+fn options_match(existing: &[ApplicationCommandOption], desired: Option<&Value>) -> bool {
+    let desired = desired.and_then(Value::as_array).map(Vec::as_slice).unwrap_or(&[]);
+    existing.len() == desired.len() && existing.iter().zip(desired).all(|(existing, option)| {
+        option["type"].as_u64() == Some(existing.kind as u64)
+            && option["name"].as_str() == Some(existing.name.as_str())
+            && option["description"].as_str() == Some(existing.description.as_str())
+            // Discord leaves out `required` when it's false, the builder only sets it when it's true.
+            && option["required"].as_bool().unwrap_or(false) == existing.required
+            && choices_match(&existing.choices, option.get("choices"))
+            && options_match(&existing.options, option.get("options"))
+    })
+}
+
+fn choices_match(existing: &[ApplicationCommandOptionChoice], desired: Option<&Value>) -> bool {
+    let desired = desired.and_then(Value::as_array).map(Vec::as_slice).unwrap_or(&[]);
+    existing.len() == desired.len() && existing.iter().zip(desired).all(|(existing, choice)| {
+        choice["name"].as_str() == Some(existing.name.as_str()) && choice["value"] == existing.value
+    })
+}
+
+fn register_commands(commands: &mut CreateApplicationCommands) -> &mut CreateApplicationCommands {
+    commands
+        .create_application_command(|command| {
+            command.name("ping").description("A ping command")
+        })
+        .create_application_command(|command| {
+            command.name("id").description("Get a user id").create_option(|option| {
+                option
+                    .name("id")
+                    .description("The user to lookup")
+                    .kind(ApplicationCommandOptionType::User)
+                    .required(true)
+            })
+        })
+        .create_application_command(|command| {
+            command
+                .name("team")
+                .description("Team options")
+                .create_option(|option| {
+                    option
+                        .name("rename")
+                        .description("Rename team.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("name")
+                                .description("New team name")
+                                .kind(ApplicationCommandOptionType::String)
+                                .required(true)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("recolor")
+                        .description("Recolor team, either by hex or by red, green and blue")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("hex")
+                                .description("Hex color like #1abc9c")
+                                .kind(ApplicationCommandOptionType::String)
+                        })
+                        .create_sub_option(|option| {
+                            option
+                                .name("red")
+                                .description("Red")
+                                .kind(ApplicationCommandOptionType::Integer)
+                        })
+                        .create_sub_option(|option| {
+                            option
+                                .name("green")
+                                .description("Green")
+                                .kind(ApplicationCommandOptionType::Integer)
+                        })
+                        .create_sub_option(|option| {
+                            option
+                                .name("blue")
+                                .description("Blue")
+                                .kind(ApplicationCommandOptionType::Integer)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("color")
+                        .description("Team color tools.")
+                        .kind(ApplicationCommandOptionType::SubCommandGroup)
+                        .create_sub_option(|option| {
+                            option
+                                .name("contrast-check")
+                                .description("Flag team colors that are hard to read or to tell apart")
+                                .kind(ApplicationCommandOptionType::SubCommand)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("copy-color")
+                        .description("Give a team the same color as another team.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("from")
+                                .description("Channel of the team to copy the color from")
+                                .kind(ApplicationCommandOptionType::Channel)
+                                .required(true)
+                        })
+                        .create_sub_option(|option| {
+                            option
+                                .name("to")
+                                .description("Channel of the team to recolor")
+                                .kind(ApplicationCommandOptionType::Channel)
+                                .required(true)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("archive")
+                        .description("Set a team aside, keeping its role, channel and score.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("channel")
+                                .description("Channel of the team")
+                                .kind(ApplicationCommandOptionType::Channel)
+                                .required(true)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("swap-scores")
+                        .description("Exchange the scores of two teams.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("a")
+                                .description("Channel of the first team")
+                                .kind(ApplicationCommandOptionType::Channel)
+                                .required(true)
+                        })
+                        .create_sub_option(|option| {
+                            option
+                                .name("b")
+                                .description("Channel of the second team")
+                                .kind(ApplicationCommandOptionType::Channel)
+                                .required(true)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("unarchive")
+                        .description("Restore an archived team.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("channel")
+                                .description("Channel of the archived team")
+                                .kind(ApplicationCommandOptionType::Channel)
+                                .required(true)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("recolor-revert")
+                        .description("Restore a team's previous color.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("channel")
+                                .description("Channel of the team")
+                                .kind(ApplicationCommandOptionType::Channel)
+                                .required(true)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("create")
+                        .description("Create a team out of an existing channel/role pair.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("channel")
+                                .description("Channel to use for team.")
+                                .kind(ApplicationCommandOptionType::Channel)
+                                .required(true)
+                        })
+                        .create_sub_option(|option| {
+                            option
+                                .name("role")
+                                .description("Role to use for team.")
+                                .kind(ApplicationCommandOptionType::Role)
+                                .required(true)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("division")
+                        .description("Team division.")
+                        .kind(ApplicationCommandOptionType::SubCommandGroup)
+                        .create_sub_option(|option| {
+                            option
+                                .name("set")
+                                .description("Place a team into a division")
+                                .kind(ApplicationCommandOptionType::SubCommand)
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("channel")
+                                        .description("Channel of the team")
+                                        .kind(ApplicationCommandOptionType::Channel)
+                                        .required(true)
+                                })
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("division")
+                                        .description("Division name")
+                                        .kind(ApplicationCommandOptionType::String)
+                                        .required(true)
+                                })
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("create-from-category")
+                        .description("Create a team for every channel in a category with a like-named role.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("category")
+                                .description("Category containing the team channels")
+                                .kind(ApplicationCommandOptionType::Channel)
+                                .required(true)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("suggest")
+                        .description("List channels that could become teams.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                })
+                .create_option(|option| {
+                    option
+                        .name("icon")
+                        .description("Team icon.")
+                        .kind(ApplicationCommandOptionType::SubCommandGroup)
+                        .create_sub_option(|option| {
+                            option
+                                .name("set")
+                                .description("Set the emoji shown next to a team's name")
+                                .kind(ApplicationCommandOptionType::SubCommand)
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("channel")
+                                        .description("Channel of the team")
+                                        .kind(ApplicationCommandOptionType::Channel)
+                                        .required(true)
+                                })
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("emoji")
+                                        .description("Unicode or custom emoji")
+                                        .kind(ApplicationCommandOptionType::String)
+                                        .required(true)
+                                })
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("motto")
+                        .description("Team motto.")
+                        .kind(ApplicationCommandOptionType::SubCommandGroup)
+                        .create_sub_option(|option| {
+                            option
+                                .name("set")
+                                .description("Set the motto shown in team info and when the team wins")
+                                .kind(ApplicationCommandOptionType::SubCommand)
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("channel")
+                                        .description("Channel of the team")
+                                        .kind(ApplicationCommandOptionType::Channel)
+                                        .required(true)
+                                })
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("text")
+                                        .description("The motto")
+                                        .kind(ApplicationCommandOptionType::String)
+                                        .required(true)
+                                })
+                        })
+                        .create_sub_option(|option| {
+                            option
+                                .name("clear")
+                                .description("Remove a team's motto")
+                                .kind(ApplicationCommandOptionType::SubCommand)
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("channel")
+                                        .description("Channel of the team")
+                                        .kind(ApplicationCommandOptionType::Channel)
+                                        .required(true)
+                                })
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("join")
+                        .description("Join a team, getting its role.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("channel")
+                                .description("Channel of the team to join")
+                                .kind(ApplicationCommandOptionType::Channel)
+                                .required(true)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("leave")
+                        .description("Leave your team, giving up its role.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                })
+                .create_option(|option| {
+                    option
+                        .name("members")
+                        .description("List the members of a team.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("channel")
+                                .description("Channel of the team, defaults to this channel")
+                                .kind(ApplicationCommandOptionType::Channel)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("list")
+                        .description("List every team with its channel, color and score.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("page")
+                                .description("Page to show, 25 teams per page")
+                                .kind(ApplicationCommandOptionType::Integer)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("info")
+                        .description("Show a team's details.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("channel")
+                                .description("Channel of the team, defaults to this channel")
+                                .kind(ApplicationCommandOptionType::Channel)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("score")
+                        .description("Team scores.")
+                        .kind(ApplicationCommandOptionType::SubCommandGroup)
+                        .create_sub_option(|option| {
+                            option
+                                .name("list")
+                                .description("View current score of teams")
+                                .kind(ApplicationCommandOptionType::SubCommand)
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("by_division")
+                                        .description("Group teams by division with subtotals")
+                                        .kind(ApplicationCommandOptionType::Boolean)
+                                })
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("compact")
+                                        .description("One line per team, easier to read on mobile")
+                                        .kind(ApplicationCommandOptionType::Boolean)
+                                })
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("format")
+                                        .description("Output format")
+                                        .kind(ApplicationCommandOptionType::String)
+                                        .add_string_choice("text", "text")
+                                        .add_string_choice("json", "json")
+                                })
+                        })
+                        .create_sub_option(|option| {
+                            option
+                                .name("watch")
+                                .description("Privately show the scoreboard and keep it updated for the next 14 minutes")
+                                .kind(ApplicationCommandOptionType::SubCommand)
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("compact")
+                                        .description("One line per team, easier to read on mobile")
+                                        .kind(ApplicationCommandOptionType::Boolean)
+                                })
+                        })
+                        .create_sub_option(|option| {
+                            option
+                                .name("projected-winner")
+                                .description("See which teams can still catch the leader")
+                                .kind(ApplicationCommandOptionType::SubCommand)
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("remaining")
+                                        .description("Number of questions left, instead of what's left in the question bank")
+                                        .kind(ApplicationCommandOptionType::Integer)
+                                })
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("max_points")
+                                        .description("Most points a single question can award, used with remaining")
+                                        .kind(ApplicationCommandOptionType::Integer)
+                                })
+                        })
+                        .create_sub_option(|option| {
+                            option
+                                .name("batch")
+                                .description("Adjust several teams at once")
+                                .kind(ApplicationCommandOptionType::SubCommand)
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("adjustments")
+                                        .description("`team amount` pairs separated by `;`, e.g. `#red +10; blue -5`")
+                                        .kind(ApplicationCommandOptionType::String)
+                                        .required(true)
+                                })
+                        })
+                        .create_sub_option(|option| {
+                            option
+                                .name("reset")
+                                .description("Reset every team's score in this server to 0, keeping the teams")
+                                .kind(ApplicationCommandOptionType::SubCommand)
+                        })
+                        .create_sub_option(|option| {
+                            option
+                                .name("set-all")
+                                .description("Set every team's score to the same value")
+                                .kind(ApplicationCommandOptionType::SubCommand)
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("value")
+                                        .description("Score to give every team")
+                                        .kind(ApplicationCommandOptionType::Integer)
+                                        .required(true)
+                                })
+                        })
+                        .create_sub_option(|option| {
+                            option
+                                .name("temp-bonus")
+                                .description("Give a team bonus points that expire after a while, replacing any current bonus")
+                                .kind(ApplicationCommandOptionType::SubCommand)
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("amount")
+                                        .description("Bonus points")
+                                        .kind(ApplicationCommandOptionType::Integer)
+                                        .required(true)
+                                })
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("seconds")
+                                        .description("How long the bonus lasts")
+                                        .kind(ApplicationCommandOptionType::Integer)
+                                        .required(true)
+                                })
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("team")
+                                        .description("Channel of the team, defaults to the current channel")
+                                        .kind(ApplicationCommandOptionType::Channel)
+                                })
+                        })
+                        .create_sub_option(|option| {
+                            option
+                                .name("adjust-percent")
+                                .description("Adjust a team's score by a percentage of its current score")
+                                .kind(ApplicationCommandOptionType::SubCommand)
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("percent")
+                                        .description("Percentage to adjust by, e.g. -25 to lose a quarter of the points")
+                                        .kind(ApplicationCommandOptionType::Integer)
+                                        .required(true)
+                                })
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("team")
+                                        .description("Channel of the team to adjust, defaults to the current channel")
+                                        .kind(ApplicationCommandOptionType::Channel)
+                                })
+                        })
+                        .create_sub_option(|option| {
+                            option
+                                .name("adjust")
+                                .description("Adjust score for team")
+                                .kind(ApplicationCommandOptionType::SubCommand)
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("amount")
+                                        .description("Amount to adjust score")
+                                        .kind(ApplicationCommandOptionType::Integer)
+                                        .required(true)
+                                })
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("team")
+                                        .description("Channel of the team to adjust, defaults to the current channel")
+                                        .kind(ApplicationCommandOptionType::Channel)
+                                })
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("amount2")
+                                        .description("Amount to adjust a second team's score")
+                                        .kind(ApplicationCommandOptionType::Integer)
+                                })
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("team2")
+                                        .description("Channel of the second team")
+                                        .kind(ApplicationCommandOptionType::Channel)
+                                })
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("amount3")
+                                        .description("Amount to adjust a third team's score")
+                                        .kind(ApplicationCommandOptionType::Integer)
+                                })
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("team3")
+                                        .description("Channel of the third team")
+                                        .kind(ApplicationCommandOptionType::Channel)
+                                })
+                        })
+                })
+        })
+        .create_application_command(|command| {
+            command
+                .name("host")
+                .description("The role allowed to run games")
+                .create_option(|option| {
+                    option
+                        .name("set")
+                        .description("Make a role the host role, only for server administrators")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("role")
+                                .description("New host role")
+                                .kind(ApplicationCommandOptionType::Role)
+                                .required(true)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("show")
+                        .description("Show the current host role")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                })
+        })
+        .create_application_command(|command| {
+            command
+                .name("config")
+                .description("Guild configuration")
+                .create_option(|option| {
+                    option
+                        .name("ephemeral")
+                        .description("Only show administrative confirmations to the invoker.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("enabled")
+                                .description("Whether confirmations are ephemeral")
+                                .kind(ApplicationCommandOptionType::Boolean)
+                                .required(true)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("reaction-scoring")
+                        .description("Let hosts adjust scores by reacting with ➕/➖, omit the channel to disable.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("channel")
+                                .description("Channel where reactions count as scoring")
+                                .kind(ApplicationCommandOptionType::Channel)
+                        })
+                        .create_sub_option(|option| {
+                            option
+                                .name("amount")
+                                .description("Points per reaction")
+                                .kind(ApplicationCommandOptionType::Integer)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("score-step")
+                        .description("Only allow adjustments in multiples of a step, omit the step to allow any amount.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("step")
+                                .description("Adjustments must be a multiple of this")
+                                .kind(ApplicationCommandOptionType::Integer)
+                        })
+                        .create_sub_option(|option| {
+                            option
+                                .name("mode")
+                                .description("What to do with other amounts, defaults to reject")
+                                .kind(ApplicationCommandOptionType::String)
+                                .add_string_choice("reject", "reject")
+                                .add_string_choice("round", "round")
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("host-role-id")
+                        .description("Use a specific role id as the host role instead of the role named Host.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("id")
+                                .description("Id of the host role")
+                                .kind(ApplicationCommandOptionType::String)
+                                .required(true)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("confirm-adjust")
+                        .description("Require naming the team when adjusting scores.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("enabled")
+                                .description("Whether the team option is required")
+                                .kind(ApplicationCommandOptionType::Boolean)
+                                .required(true)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("export")
+                        .description("Export this guild's settings as JSON, for `/config import` in another guild.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                })
+                .create_option(|option| {
+                    option
+                        .name("import")
+                        .description("Replace this guild's settings with ones from `/config export`.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("json")
+                                .description("Output of `/config export`")
+                                .kind(ApplicationCommandOptionType::String)
+                                .required(true)
+                        })
+                })
+        })
+        .create_application_command(|command| {
+            command
+                .name("score")
+                .description("Shared score for cooperative games")
+                .create_option(|option| {
+                    option
+                        .name("show")
+                        .description("Show the shared score.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                })
+                .create_option(|option| {
+                    option
+                        .name("adjust")
+                        .description("Adjust the shared score.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("amount")
+                                .description("Amount to adjust score")
+                                .kind(ApplicationCommandOptionType::Integer)
+                                .required(true)
+                        })
+                })
+        })
+        .create_application_command(|command| {
+            command
+                .name("player")
+                .description("Player options")
+                .create_option(|option| {
+                    option
+                        .name("stats")
+                        .description("Points a player's teams earned while they were a member.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("user")
+                                .description("The player to look up")
+                                .kind(ApplicationCommandOptionType::User)
+                                .required(true)
+                        })
+                })
+        })
+        .create_application_command(|command| {
+            command
+                .name("scoreboard")
+                .description("Scoreboard presentation")
+                .create_option(|option| {
+                    option
+                        .name("order")
+                        .description("Show teams in a fixed order instead of by score.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("teams")
+                                .description("Teams in display order separated by spaces, e.g. `#red #blue`, or `auto` to sort by score")
+                                .kind(ApplicationCommandOptionType::String)
+                                .required(true)
+                        })
+                })
+        })
+        .create_application_command(|command| {
+            // Commands with subcommands can't be run on their own, so buzzing in is `/buzz in` rather than `/buzz`.
+            command
+                .name("buzz")
+                .description("Buzzer for the channel's question")
+                .create_option(|option| {
+                    option
+                        .name("in")
+                        .description("Buzz in to claim the question for your team.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                })
+                .create_option(|option| {
+                    option
+                        .name("clear")
+                        .description("Release the buzzer so other teams can buzz in.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                })
+        })
+        .create_application_command(|command| {
+            command
+                .name("trivia")
+                .description("Trivia options")
+                .create_option(|option| {
+                    option
+                        .name("leaderboard")
+                        .description("All-time game wins per team.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                })
+                .create_option(|option| {
+                    option
+                        .name("ask")
+                        .description("Ask a random question that hasn't come up yet.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("category")
+                                .description("Only ask a question from this category")
+                                .kind(ApplicationCommandOptionType::String)
+                        })
+                        .create_sub_option(|option| {
+                            option
+                                .name("exclude")
+                                .description("Categories to leave out, separated by commas, ignored when a category is given")
+                                .kind(ApplicationCommandOptionType::String)
+                        })
+                        .create_sub_option(|option| {
+                            option
+                                .name("timer")
+                                .description("Seconds until the answer is revealed, from 5 to 600")
+                                .kind(ApplicationCommandOptionType::Integer)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("fetch")
+                        .description("Add random questions from Open Trivia DB to the question bank.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("amount")
+                                .description("How many questions to add, up to 50")
+                                .kind(ApplicationCommandOptionType::Integer)
+                                .required(true)
+                        })
+                        .create_sub_option(|option| {
+                            option
+                                .name("category")
+                                .description("Open Trivia DB category number, e.g. 9 for general knowledge")
+                                .kind(ApplicationCommandOptionType::Integer)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("categories")
+                        .description("List the question categories and how many questions are left in each.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                })
+                .create_option(|option| {
+                    option
+                        .name("answer")
+                        .description("Answer this channel's question, scoring for the team that buzzed in or the channel's team.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("text")
+                                .description("Your answer")
+                                .kind(ApplicationCommandOptionType::String)
+                                .required(true)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("points")
+                        .description("Set how many points a correct answer earns at a difficulty.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("difficulty")
+                                .description("Question difficulty")
+                                .kind(ApplicationCommandOptionType::Integer)
+                                .required(true)
+                        })
+                        .create_sub_option(|option| {
+                            option
+                                .name("points")
+                                .description("Points for a correct answer, leave out for the default of 100 per difficulty level")
+                                .kind(ApplicationCommandOptionType::Integer)
+                        })
+                })
+        })
+        .create_application_command(|command| {
+            command
+                .name("game")
+                .description("Game options")
+                .create_option(|option| {
+                    option
+                        .name("start")
+                        .description("Start a game, allowing scoring when the guild requires an active game.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                })
+                .create_option(|option| {
+                    option
+                        .name("end")
+                        .description("End the game, crowning the highest scoring team on the leaderboard.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                })
+                .create_option(|option| {
+                    option
+                        .name("config")
+                        .description("Game rules.")
+                        .kind(ApplicationCommandOptionType::SubCommandGroup)
+                        .create_sub_option(|option| {
+                            option
+                                .name("mode")
+                                .description("Switch between versus and cooperative scoring")
+                                .kind(ApplicationCommandOptionType::SubCommand)
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("mode")
+                                        .description("Scoring mode")
+                                        .kind(ApplicationCommandOptionType::String)
+                                        .add_string_choice("versus", "versus")
+                                        .add_string_choice("coop", "coop")
+                                        .required(true)
+                                })
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("target")
+                                        .description("Points the server is aiming for in cooperative mode")
+                                        .kind(ApplicationCommandOptionType::Integer)
+                                })
+                        })
+                        .create_sub_option(|option| {
+                            option
+                                .name("require-active")
+                                .description("Refuse scoring commands unless a game has been started")
+                                .kind(ApplicationCommandOptionType::SubCommand)
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("enabled")
+                                        .description("Whether scoring requires an active game")
+                                        .kind(ApplicationCommandOptionType::Boolean)
+                                        .required(true)
+                                })
+                        })
+                        .create_sub_option(|option| {
+                            option
+                                .name("percent-rounding")
+                                .description("How percentage adjustments round to whole points")
+                                .kind(ApplicationCommandOptionType::SubCommand)
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("mode")
+                                        .description("Rounding mode")
+                                        .kind(ApplicationCommandOptionType::String)
+                                        .add_string_choice("nearest", "nearest")
+                                        .add_string_choice("down", "down")
+                                        .add_string_choice("up", "up")
+                                        .required(true)
+                                })
+                        })
+                        .create_sub_option(|option| {
+                            option
+                                .name("max-team-score")
+                                .description("Cap team scores, omit the value to uncap")
+                                .kind(ApplicationCommandOptionType::SubCommand)
+                                .create_sub_option(|option| {
+                                    option
+                                        .name("value")
+                                        .description("Highest score a team can reach")
+                                        .kind(ApplicationCommandOptionType::Integer)
+                                })
+                        })
+                })
+        })
+        .create_application_command(|command| {
+            command
+                .name("admin")
+                .description("Bot owner commands")
+                .create_option(|option| {
+                    option
+                        .name("shutdown")
+                        .description("Stop the bot.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                })
+                .create_option(|option| {
+                    option
+                        .name("debug-interaction")
+                        .description("Show the options the previous command in this guild was received with.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                })
+                .create_option(|option| {
+                    option
+                        .name("diag")
+                        .description("Check this guild's scores against the scoring audit.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                })
+                .create_option(|option| {
+                    option
+                        .name("guilds")
+                        .description("List the guilds the bot is in.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("page")
+                                .description("Page to show, defaults to the first")
+                                .kind(ApplicationCommandOptionType::Integer)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("reregister")
+                        .description("Re-register slash commands.")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("global")
+                                .description("Register globally instead of for this guild")
+                                .kind(ApplicationCommandOptionType::Boolean)
+                        })
+                })
+        })
+}
+
+#[async_trait]
+impl EventHandler for Handler {
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        if interaction.kind == InteractionType::ApplicationCommand {
+            if let Some(data) = interaction.data.as_ref() {
+                let debugging = data.name == "admin" && data.options.get(0).map(|option| option.name.as_str()) == Some("debug-interaction");
+                if let (Some(guild_id), false) = (interaction.guild_id, debugging) {
+                    self.last_interaction.lock().unwrap().insert(guild_id, describe_interaction(data));
+                }
+
+                let ephemeral = self.is_ephemeral(interaction.guild_id, command_category(data));
+
+                // Acknowledged before running the command, Discord fails interactions it hasn't heard back about in 3
+                // seconds and role edits alone can take longer than that.
+                let deferred = match try_defer(&ctx, &interaction, ephemeral).await {
+                    Ok(deferred_ephemeral) => Some(deferred_ephemeral),
+                    Err(why) => {
+                        error!(guild_id = ?interaction.guild_id, channel_id = ?interaction.channel_id, user_id = ?interaction_user_id(&interaction), error = %why, "Cannot defer slash command");
+                        None
+                    },
+                };
+                let result = self.run_command(&ctx, &interaction, data).await;
+
+                // Failures only concern whoever ran the command, there's no need to interrupt the game with them.
+                let (response, ephemeral) = match result {
+                    Ok(response) => (response, ephemeral),
+                    Err(err) => (Response::Text(err.to_string()), true),
+                };
+
+                self.respond(&ctx, &interaction, response, ephemeral, deferred).await;
+                self.save_teams();
+                self.refresh_score_watches(&ctx).await;
+
+                // Shut down only after acknowledging, otherwise the process can exit before the response is sent.
+                let shutdown = data.name == "admin" && data.options.get(0).map(|option| option.name.as_str()) == Some("shutdown");
+                if shutdown && self.is_owner(&interaction) {
+                    self.shutdown(&ctx, &interaction).await;
+                }
+            }
+        }
+    }
+
+    async fn guild_create(&self, ctx: Context, guild: Guild, is_new: bool) {
+        self.teams.lock().unwrap().resolve_pending(guild.id, &guild.roles);
+
+        self.resolve_host_role(guild.id, &guild.roles);
+
+        // `is_new` is only set when the bot joins, reconnects replay guild creates for every guild.
+        if !is_new {
+            return
+        }
+
+        // Only errors for a guild that's new to the set, so it's still welcomed this once.
+        match self.welcomed.lock().unwrap().insert(guild.id) {
+            Ok(true) => {},
+            Ok(false) => return,
+            Err(err) => warn!(guild_id = %guild.id, error = %err, "Cannot save welcomed guilds"),
+        }
+
+        let mut text_channels: Vec<_> = guild.channels
+            .values()
+            .filter(|channel| channel.kind == ChannelType::Text)
+            .collect();
+        text_channels.sort_by_key(|channel| channel.position);
+
+        let candidates = guild.system_channel_id
+            .into_iter()
+            .chain(text_channels.into_iter().map(|channel| channel.id));
+
+        // Rather than working out permissions up front, try each channel until one accepts the message.
+        for channel_id in candidates {
+            if channel_id.say(&ctx.http, WELCOME).await.is_ok() {
+                return
+            }
+        }
+
+        warn!(guild_id = %guild.id, "Cannot post welcome message, no writable channel");
+    }
+
+    async fn reaction_add(&self, ctx: Context, reaction: Reaction) {
+        let (guild_id, user_id) = match (reaction.guild_id, reaction.user_id) {
+            (Some(guild_id), Some(user_id)) => (guild_id, user_id),
+            _ => return,
+        };
+
+        let amount = match self.guild_config(Some(guild_id)).reaction_scoring {
+            Some((channel_id, amount)) if channel_id == reaction.channel_id => amount,
+            _ => return,
+        };
+
+        let delta = match &reaction.emoji {
+            ReactionType::Unicode(emoji) if emoji == "➕" => amount,
+            ReactionType::Unicode(emoji) if emoji == "➖" => -amount,
+            _ => return,
+        };
+
+        let reactor = match user_id.to_user(&ctx).await {
+            Ok(user) => user,
+            Err(_) => return,
+        };
+        if reactor.bot || !self.user_is_host(&ctx, guild_id, &reactor).await {
+            return
+        }
+
+        let message = match reaction.message(&ctx.http).await {
+            Ok(message) => message,
+            Err(why) => {
+                warn!(guild_id = ?reaction.guild_id, channel_id = %reaction.channel_id, message_id = %reaction.message_id, error = %why, "Cannot fetch reacted message");
+                return
+            },
+        };
+        let author = match guild_id.member(&ctx, message.author.id).await {
+            Ok(member) => member,
+            Err(_) => return,
+        };
+
+        if !self.scoring_allowed(Some(guild_id)) {
+            return
+        }
+
+        let config = self.guild_config(Some(guild_id));
+        let delta = match apply_score_step(delta, &config) {
+            Ok((delta, _)) => delta,
+            Err(_) => return,
+        };
+        // In a cooperative game every reaction counts towards the shared score, whoever's message it is.
+        let result = if config.mode == GameMode::Coop {
+            Some((self.adjust_coop_score(guild_id, delta), Vec::new()))
+        } else {
+            let team_channel = self.teams.lock().unwrap().team_for_roles(&author.roles);
+            team_channel.and_then(|team_channel| self.score_team(&team_channel, |team| {
+                match add_to_score(team.score, delta, &config) {
+                    Some((score, clamped)) => {
+                        let applied = score.saturating_sub(team.score);
+                        team.score = score;
+                        let content = format!("Adjusted **{}** by {:+} → {}", team.display_name(), delta, team.score);
+                        if clamped {
+                            (format!("{} (capped at the maximum team score)", content), vec![(team.role.id, applied)])
+                        } else {
+                            (content, vec![(team.role.id, applied)])
+                        }
+                    },
+                    None => (format!("Cannot adjust **{}** by {:+}, score out of range", team.display_name(), delta), Vec::new()),
+                }
+            }, |(_, applied)| applied.iter().map(|(_, delta)| delta).sum()))
+        };
+
+        if let Some((content, applied)) = result {
+            self.credit_members(&ctx, guild_id, &applied).await;
+            if let Err(why) = reaction.channel_id.send_message(&ctx.http, |message| message.content(content).allowed_mentions(no_pings)).await {
+                warn!(guild_id = ?reaction.guild_id, channel_id = %reaction.channel_id, error = %why, "Cannot announce reaction adjustment");
+            }
+            self.save_teams();
+            self.refresh_score_watches(&ctx).await;
+        }
+    }
+
+    async fn typing_start(&self, ctx: Context, start: TypingStartEvent) {
+        if !self.mimic {
+            return
+        }
+
+        {
+            let cache: &Cache = ctx.as_ref();
+            if start.user_id == cache.current_user_id().await {
+                return
+            }
+        }
+
+        if let Ok(user) = start.user_id.to_user(ctx.clone()).await {
+            debug!(guild_id = ?start.guild_id, channel_id = %start.channel_id, user_id = %user.id, user = %user.name, "Mimicking typing");
+            if let Err(why) = start.channel_id.broadcast_typing(ctx).await {
+                warn!(channel_id = %start.channel_id, error = %why, "Cannot mimic typing");
+            }
+        }
+    }
+
+    async fn ready(&self, ctx: Context, ready: Ready) {
+        info!(user = %ready.user.name, guilds = ready.guilds.len(), "Connected");
+
+        // The guilds here are mostly unavailable placeholders, host roles are resolved once each one's `guild_create`
+        // comes in with its roles.
+        for guild in ready.guilds {
+            // Re-registering briefly makes the commands unavailable and eats into rate limits, so only do it when they changed.
+            let up_to_date = !self.force_command_sync && match guild.id().get_application_commands(&ctx.http).await {
+                Ok(existing) => {
+                    let mut desired = CreateApplicationCommands::default();
+                    register_commands(&mut desired);
+                    commands_match(&existing, &desired.0)
+                },
+                Err(why) => {
+                    warn!(guild_id = %guild.id(), error = %why, "Cannot fetch commands");
+                    false
+                },
+            };
+
+            if up_to_date {
+                info!(guild_id = %guild.id(), "Commands are up to date");
+            } else {
+                match guild.id().set_application_commands(&ctx.http, register_commands).await {
+                    Ok(commands) => info!(guild_id = %guild.id(), commands = commands.len(), "Registered commands"),
+                    Err(why) => error!(guild_id = %guild.id(), error = %why, "Cannot register commands"),
+                }
+            }
+        }
+    }
+
+    // Every guild has sent its `guild_create` by now, so anything still pending belongs to a guild the bot has left or
+    // lost access to.
+    async fn cache_ready(&self, _ctx: Context, guilds: Vec<GuildId>) {
+        let teams = self.teams.lock().unwrap();
+        info!(guilds = guilds.len(), teams = teams.teams.len(), pending = teams.pending.len(), "Cache is ready");
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    // RUST_LOG picks what gets logged, e.g. `RUST_LOG=trivia_bot=debug` to see mimicking too.
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .init();
+
+    // Configure the client with your Discord bot token in the environment.
+    let token = env::var("DISCORD_TOKEN").expect("Expected a token in the environment");
+
+    // The Application Id is usually the Bot User Id.
+    let config = BotConfig::load().unwrap_or_else(|err| panic!("{}", err));
+    let application_id = config.application_id.expect("Expected an application id");
+
+    let leaderboard_path = PathBuf::from(config.leaderboard_file.as_deref().unwrap_or("leaderboard.json"));
+    let leaderboard = Leaderboard::load(&leaderboard_path)
+        .unwrap_or_else(|err| panic!("Cannot load leaderboard {}: {}", leaderboard_path.display(), err));
+
+    let questions_path = PathBuf::from(config.questions_file.as_deref().unwrap_or("questions.json"));
+    let (questions, duplicates) = Questions::load(&questions_path)
+        .unwrap_or_else(|err| panic!("Cannot load questions {}: {}", questions_path.display(), err));
+    info!(questions = questions.len(), duplicates = duplicates, path = %questions_path.display(), "Loaded question bank");
+
+    let teams_path = PathBuf::from(config.teams_file.as_deref().unwrap_or("teams.json"));
+    let teams = Teams::load_from_path(&teams_path)
+        .unwrap_or_else(|err| panic!("Cannot load teams {}: {}", teams_path.display(), err));
+    // Shared with the handler, so the teams can still be saved once the client has stopped.
+    let teams = Arc::new(Mutex::new(teams));
+
+    let welcomed_path = teams_path.with_file_name("welcomed.json");
+    let welcomed = Welcomed::load(&welcomed_path)
+        .unwrap_or_else(|err| panic!("Cannot load welcomed guilds {}: {}", welcomed_path.display(), err));
+
+    let guilds_path = teams_path.with_file_name("guilds.json");
+    let guilds = SavedGuilds::load(&guilds_path)
+        .unwrap_or_else(|err| panic!("Cannot load guild settings {}: {}", guilds_path.display(), err));
+
+    let mut intents = GatewayIntents::GUILDS | GatewayIntents::GUILD_MESSAGE_REACTIONS;
+    // Typing events are by far the noisiest thing we receive, so they are only requested while mimicking is on.
+    if config.mimic_enabled {
+        intents |= GatewayIntents::GUILD_MESSAGE_TYPING | GatewayIntents::DIRECT_MESSAGE_TYPING;
+    }
+    // Player stats only see cached members, caching all of them needs the privileged members intent.
+    if config.members_intent {
+        intents |= GatewayIntents::GUILD_MEMBERS;
+    }
+    info!(?intents, "Requesting gateway intents");
+
+    // Build our client.
+    let mut client = Client::builder(token)
+        .event_handler(Handler::new(&config, leaderboard, teams.clone(), questions, welcomed, guilds))
+        .application_id(application_id)
+        .intents(intents)
+        .await
+        .expect("Error creating client");
+
+    {
+        let mut data = client.data.write().await;
+        data.insert::<ShardManagerContainer>(client.shard_manager.clone());
+    }
+
+    let shard_manager = client.shard_manager.clone();
+    tokio::spawn(async move {
+        if let Err(why) = tokio::signal::ctrl_c().await {
+            error!(error = %why, "Cannot listen for Ctrl-C");
+            return
+        }
+
+        info!("Ctrl-C received, shutting down");
+        shard_manager.lock().await.shutdown_all().await;
+    });
+
+    // Finally, start a single shard, and start listening to events.
+    //
+    // Shards will automatically attempt to reconnect, and will perform
+    // exponential backoff until it reconnects.
+    if let Err(why) = client.start().await {
+        error!(error = ?why, "Client error");
+    }
+
+    // However the client stopped, Ctrl-C or `/admin shutdown`, the latest scores shouldn't be lost with it.
+    let teams = teams.lock().unwrap();
+    match teams.save_to_path(&teams.path) {
+        Ok(()) => info!(path = %teams.path.display(), "Saved teams, shut down gracefully"),
+        Err(why) => error!(path = %teams.path.display(), error = %why, "Cannot save teams on shutdown"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serenity::builder::{CreateInteractionResponseData, ParseValue};
+
+    const GUILD: GuildId = GuildId(10);
+    const OTHER_GUILD: GuildId = GuildId(20);
+
+    // Roles can only be built the way Discord sends them.
+    fn role(guild_id: GuildId, role_id: u64, name: &str) -> Role {
+        serde_json::from_value(serde_json::json!({
+            "id": role_id,
+            "guild_id": guild_id.0,
+            "color": 0,
+            "hoist": false,
+            "managed": false,
+            "mentionable": false,
+            "name": name,
+            "permissions": 0,
+            "position": 1,
+        }))
+        .unwrap()
+    }
+
+    // Adds a team for `channel_id`, using the channel id for its role id as well.
+    fn add_team(teams: &mut Teams, guild_id: GuildId, channel_id: u64, name: &str, score: i64) {
+        teams.create_team(ChannelId(channel_id), role(guild_id, channel_id, name)).unwrap();
+        teams.teams.get_mut(&ChannelId(channel_id)).unwrap().score = score;
+    }
+
+    #[test]
+    fn scoreboard_is_reused_until_teams_change() {
+        let mut teams = Teams::new();
+        add_team(&mut teams, GUILD, 1, "Red", 10);
+        let options = ScoreboardOptions::default();
+        let first = teams.scoreboard(GUILD, options);
+
+        // Changed without touching, so only a cached render still shows the old score.
+        teams.teams.get_mut(&ChannelId(1)).unwrap().score = 20;
+        assert_eq!(teams.scoreboard(GUILD, options), first);
+
+        teams.touch();
+        let second = teams.scoreboard(GUILD, options);
+        assert_ne!(second, first);
+        assert!(second.contains("Red: 20"));
+    }
+
+    #[test]
+    fn scoreboard_is_cached_per_guild() {
+        let mut teams = Teams::new();
+        add_team(&mut teams, GUILD, 1, "Red", 10);
+        add_team(&mut teams, OTHER_GUILD, 2, "Blue", 5);
+        let options = ScoreboardOptions::default();
+
+        let guild = teams.scoreboard(GUILD, options);
+        let other_guild = teams.scoreboard(OTHER_GUILD, options);
+        assert!(guild.contains("Red") && !guild.contains("Blue"));
+        assert!(other_guild.contains("Blue") && !other_guild.contains("Red"));
+        assert_eq!(teams.scoreboard(GUILD, options), guild);
+    }
+
+    fn scores(scores: &[(&str, i64)]) -> Vec<(String, i64)> {
+        scores.iter().map(|(name, score)| (name.to_string(), *score)).collect()
+    }
+
+    #[test]
+    fn projected_contenders_needs_teams() {
+        assert!(projected_contenders(&[], 30).is_none());
+    }
+
+    #[test]
+    fn projected_contenders_without_points_left() {
+        let scores = scores(&[("Red", 30), ("Blue", 20), ("Green", 30)]);
+        let (leader, trailing) = projected_contenders(&scores, 0).unwrap();
+        assert_eq!(leader.1, 30);
+
+        // Only a team already level with the leader can still share the win.
+        let outcomes: Vec<(&str, i64, bool)> = trailing.iter().map(|(entry, deficit, can_win)| (entry.0.as_str(), *deficit, *can_win)).collect();
+        assert_eq!(outcomes.len(), 2);
+        assert!(outcomes.contains(&("Blue", 10, false)));
+        assert!(outcomes.iter().any(|(name, deficit, can_win)| *name != "Blue" && *deficit == 0 && *can_win));
+    }
+
+    #[test]
+    fn projected_contenders_with_points_left() {
+        let scores = scores(&[("Red", 50), ("Blue", 30), ("Green", 10)]);
+        let (leader, trailing) = projected_contenders(&scores, 20).unwrap();
+        assert_eq!(leader.0, "Red");
+        assert!(trailing.iter().any(|(entry, deficit, can_win)| entry.0 == "Blue" && *deficit == 20 && *can_win));
+        assert!(trailing.iter().any(|(entry, deficit, can_win)| entry.0 == "Green" && *deficit == 40 && !*can_win));
+    }
+
+    #[test]
+    fn sorted_teams_breaks_ties_by_name() {
+        let mut teams = Teams::new();
+        add_team(&mut teams, GUILD, 1, "Red", 10);
+        add_team(&mut teams, GUILD, 2, "Blue", 10);
+        add_team(&mut teams, GUILD, 3, "Green", 20);
+        add_team(&mut teams, OTHER_GUILD, 4, "Purple", 30);
+
+        let names: Vec<&str> = teams.sorted_teams(GUILD).iter().map(|(_, team)| team.role.name.as_str()).collect();
+        assert_eq!(names, vec!["Green", "Blue", "Red"]);
+    }
+
+    #[test]
+    fn projected_contenders_does_not_overflow() {
+        let scores = scores(&[("Red", i64::MAX), ("Blue", i64::MIN)]);
+        let (_, trailing) = projected_contenders(&scores, i64::MAX).unwrap();
+        assert_eq!(trailing[0].1, i64::MAX);
+        assert!(trailing[0].2);
+    }
+
+    #[test]
+    fn add_to_score_rejects_overflow() {
+        let config = GuildConfig::default();
+        assert_eq!(add_to_score(i64::MAX - 1, 1, &config), Some((i64::MAX, false)));
+        assert_eq!(add_to_score(i64::MAX, 1, &config), None);
+        assert_eq!(add_to_score(i64::MIN, -1, &config), None);
+    }
+
+    #[test]
+    fn add_to_score_caps_at_the_maximum() {
+        let config = GuildConfig { max_team_score: Some(100), ..GuildConfig::default() };
+        assert_eq!(add_to_score(90, 20, &config), Some((100, true)));
+        assert_eq!(add_to_score(i64::MAX, 1, &config), None);
+    }
+
+    #[test]
+    fn percent_of_zero_score() {
+        for rounding in &[Rounding::Down, Rounding::Up, Rounding::Nearest] {
+            assert_eq!(percent_of(0, 50, *rounding), Some(0));
+            assert_eq!(percent_of(0, -200, *rounding), Some(0));
+        }
+    }
+
+    #[test]
+    fn percent_of_rounding() {
+        // 10% of 25 is 2.5, a tie for nearest.
+        assert_eq!(percent_of(25, 10, Rounding::Down), Some(2));
+        assert_eq!(percent_of(25, 10, Rounding::Up), Some(3));
+        assert_eq!(percent_of(25, 10, Rounding::Nearest), Some(3));
+        assert_eq!(percent_of(24, 10, Rounding::Nearest), Some(2));
+
+        // Rounding works on the number line, so down moves away from zero for losses.
+        assert_eq!(percent_of(25, -10, Rounding::Down), Some(-3));
+        assert_eq!(percent_of(25, -10, Rounding::Up), Some(-2));
+        assert_eq!(percent_of(25, -10, Rounding::Nearest), Some(-2));
+    }
+
+    #[test]
+    fn percent_of_out_of_range() {
+        assert_eq!(percent_of(i64::MAX, 100, Rounding::Down), Some(i64::MAX));
+        assert_eq!(percent_of(i64::MAX, 200, Rounding::Down), None);
+        assert_eq!(percent_of(i64::MIN, -100, Rounding::Up), None);
+    }
+
+    #[test]
+    fn no_pings_allows_no_mentions() {
+        let mut mentions = CreateAllowedMentions::default();
+        no_pings(&mut mentions);
+
+        // Nothing to parse and no roles or users listed, so `@everyone` and `<@&role>` in a team name stay plain text.
+        assert_eq!(mentions.0.get("parse"), Some(&serde_json::json!([])));
+        assert!(!mentions.0.contains_key("roles"));
+        assert!(!mentions.0.contains_key("users"));
+    }
+
+    #[test]
+    fn no_pings_drops_earlier_mentions() {
+        let mut mentions = CreateAllowedMentions::default();
+        mentions.parse(ParseValue::Everyone).parse(ParseValue::Roles);
+        no_pings(&mut mentions);
+        assert_eq!(mentions.0.get("parse"), Some(&serde_json::json!([])));
+    }
+
+    #[test]
+    fn everyone_team_name_does_not_ping() {
+        let mut teams = Teams::new();
+        add_team(&mut teams, GUILD, 1, "@everyone", 10);
+        let content = teams.scoreboard(GUILD, ScoreboardOptions::default());
+        assert!(content.contains("@everyone: 10"));
+
+        // Built the same way `send_response` does, the name is shown as is but can't mention anyone.
+        let mut message = CreateInteractionResponseData::default();
+        message.content(&content).allowed_mentions(no_pings);
+        assert_eq!(message.0.get("allowed_mentions"), Some(&serde_json::json!({ "parse": [] })));
+    }
+
+    #[test]
+    fn swap_scores_exchanges_scores() {
+        let mut teams = Teams::new();
+        add_team(&mut teams, GUILD, 1, "Red", 10);
+        add_team(&mut teams, GUILD, 2, "Blue", -3);
+        let version = teams.version;
+
+        let swapped = teams.swap_scores(&ChannelId(1), &ChannelId(2));
+        assert_eq!(swapped, Some((("Red".to_string(), -3), ("Blue".to_string(), 10))));
+        assert_eq!(teams.teams[&ChannelId(1)].score, -3);
+        assert_eq!(teams.teams[&ChannelId(2)].score, 10);
+        assert!(teams.version > version);
+    }
+
+    #[test]
+    fn swap_scores_with_itself_or_a_missing_team() {
+        let mut teams = Teams::new();
+        add_team(&mut teams, GUILD, 1, "Red", 10);
+        let version = teams.version;
+
+        assert_eq!(teams.swap_scores(&ChannelId(1), &ChannelId(1)), None);
+        assert_eq!(teams.swap_scores(&ChannelId(1), &ChannelId(2)), None);
+        assert_eq!(teams.swap_scores(&ChannelId(2), &ChannelId(1)), None);
+        assert_eq!(teams.teams[&ChannelId(1)].score, 10);
+        // Nothing changed, so cached scoreboards stay valid.
+        assert_eq!(teams.version, version);
+    }
+
+    #[test]
+    fn sanitize_motto_breaks_mentions() {
+        let motto = sanitize_motto("@everyone  look at\n<@&42>").unwrap();
+        assert_eq!(motto, "@\u{200B}everyone look at <@\u{200B}&42>");
+    }
+
+    fn handler() -> Handler {
+        Handler::new(&BotConfig::default(), Leaderboard::default(), Arc::new(Mutex::new(Teams::new())), Questions::default(), Welcomed::default(), SavedGuilds::default())
+    }
+
+    fn roles(roles: &[Role]) -> HashMap<RoleId, Role> {
+        roles.iter().map(|role| (role.id, role.clone())).collect()
+    }
+
+    #[test]
+    fn host_roles_are_kept_per_guild() {
+        let handler = handler();
+        // The other guild picked its own host role with `/host set`, its "Host" role is just a team.
+        handler.fix_host_role(OTHER_GUILD, RoleId(2));
+
+        handler.resolve_host_role(GUILD, &roles(&[role(GUILD, 1, "Host"), role(GUILD, 4, "Red")]));
+        handler.resolve_host_role(OTHER_GUILD, &roles(&[role(OTHER_GUILD, 2, "Quizmaster"), role(OTHER_GUILD, 3, "Host")]));
+
+        assert_eq!(handler.guild_host_role(GUILD), Some(RoleId(1)));
+        assert_eq!(handler.guild_host_role(OTHER_GUILD), Some(RoleId(2)));
+    }
+
+    #[test]
+    fn host_role_is_dropped_with_the_role() {
+        let handler = handler();
+        handler.resolve_host_role(GUILD, &roles(&[role(GUILD, 1, "Host")]));
+        handler.resolve_host_role(OTHER_GUILD, &roles(&[role(OTHER_GUILD, 2, "Host")]));
+
+        handler.resolve_host_role(GUILD, &roles(&[role(GUILD, 4, "Red")]));
+        assert_eq!(handler.guild_host_role(GUILD), None);
+        assert_eq!(handler.guild_host_role(OTHER_GUILD), Some(RoleId(2)));
+    }
+
+    fn question(prompt: &str, answer: &str) -> questions::Question {
+        questions::Question { prompt: prompt.to_string(), answers: vec![answer.to_string()], category: "General".to_string(), difficulty: 1 }
+    }
+
+    #[test]
+    fn dedupe_questions_keeps_the_first() {
+        let questions = questions::dedupe_questions(vec![
+            question("What is the capital of France?", "Paris"),
+            question("Who painted the Mona Lisa?", "Leonardo da Vinci"),
+            question("  what is the capital of   FRANCE? ", "paris"),
+        ]);
+
+        let answers: Vec<&str> = questions.iter().map(|question| question.answer()).collect();
+        assert_eq!(answers, vec!["Paris", "Leonardo da Vinci"]);
+    }
+
+    #[test]
+    fn any_accepted_answer_matches() {
+        let question = questions::Question {
+            answers: vec!["New York City".to_string(), "NYC".to_string(), "New York".to_string()],
+            ..question("Which city is the Big Apple?", "")
+        };
+
+        assert_eq!(question.answer(), "New York City");
+        assert!(question.is_correct("nyc"));
+        assert!(question.is_correct("new york"));
+        assert!(!question.is_correct("Boston"));
+    }
+
+    #[test]
+    fn single_answer_questions_still_load() {
+        let bank: Vec<questions::Question> = serde_json::from_str(r#"[
+            {"prompt": "What is the capital of France?", "answer": "Paris", "category": "Geography", "difficulty": 1},
+            {"prompt": "Which city is the Big Apple?", "answers": ["New York City", "NYC"], "category": "Geography", "difficulty": 1}
+        ]"#).unwrap();
+
+        assert_eq!(bank[0].answers, vec!["Paris".to_string()]);
+        assert_eq!(bank[1].answers, vec!["New York City".to_string(), "NYC".to_string()]);
+    }
+
+    #[test]
+    fn is_valid_emoji_needs_an_emoji() {
+        for emoji in &["🔥", "❤️", "👨‍👩‍👧‍👦", "🇳🇱", "#️⃣", "<:party:123>", "<a:wave:456>"] {
+            assert!(is_valid_emoji(emoji), "{} should be allowed", emoji);
+        }
+        for emoji in &["", "é", "ß", "日本", "#", "1⃣1", "<:party:>", "🔥a"] {
+            assert!(!is_valid_emoji(emoji), "{} should be rejected", emoji);
+        }
+    }
+}
@@ -0,0 +1,381 @@
+use std::{
+    collections::{
+        hash_map::{DefaultHasher, RandomState},
+        BTreeMap,
+        HashMap,
+        HashSet,
+    },
+    fmt, io,
+    hash::{BuildHasher, Hash, Hasher},
+    path::Path,
+};
+
+use serde::{Deserialize, Deserializer};
+use serenity::model::id::GuildId;
+
+use crate::storage::load_json;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Question {
+    pub prompt: String,
+    /// Every accepted answer, the first is the one revealed. Older banks have a single `answer` string instead.
+    #[serde(alias = "answer", deserialize_with = "one_or_many")]
+    pub answers: Vec<String>,
+    /// Empty for questions that weren't given one.
+    #[serde(default)]
+    pub category: String,
+    pub difficulty: u8,
+}
+
+impl Question {
+    /// The answer shown when the question is revealed.
+    pub fn answer(&self) -> &str {
+        self.answers.first().map_or("", String::as_str)
+    }
+
+    /// Whether `submitted` matches any of the accepted answers.
+    pub fn is_correct(&self, submitted: &str) -> bool {
+        self.answers.iter().any(|answer| answer_matches(submitted, answer))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(answer) => vec![answer],
+        OneOrMany::Many(answers) => answers,
+    })
+}
+
+/// The question bank, along with which questions each guild has already been asked.
+///
+/// Questions are referred to by their index in the bank, which stays stable since the bank is only ever added to.
+#[derive(Debug, Default)]
+pub struct Questions {
+    questions: Vec<Question>,
+    used: HashMap<GuildId, HashSet<usize>>,
+}
+
+impl Questions {
+    /// Reads the question bank at `path`, a JSON array of questions. A missing file is an empty bank. Questions
+    /// repeating an earlier prompt are dropped, the number dropped is returned along with the bank.
+    pub fn load(path: &Path) -> io::Result<(Questions, usize)> {
+        let questions: Vec<Question> = load_json(path)?.unwrap_or_default();
+        let count = questions.len();
+        let questions = dedupe_questions(questions);
+        let dropped = count - questions.len();
+
+        Ok((Questions { questions: questions, used: HashMap::new() }, dropped))
+    }
+
+    /// Adds `questions` to the end of the bank, none of them have been asked yet. Questions whose prompt is
+    /// already in the bank are left out, returns how many were added.
+    pub fn extend(&mut self, questions: Vec<Question>) -> usize {
+        let before = self.questions.len();
+        let mut combined = std::mem::take(&mut self.questions);
+        combined.extend(questions);
+        // The bank itself has no duplicates, so only new questions are dropped and indices stay the same.
+        self.questions = dedupe_questions(combined);
+        self.questions.len() - before
+    }
+
+    pub fn len(&self) -> usize {
+        self.questions.len()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Question> {
+        self.questions.get(index)
+    }
+
+    /// Questions `guild` hasn't been asked yet.
+    pub fn remaining(&self, guild: GuildId) -> usize {
+        self.questions.len() - self.used.get(&guild).map_or(0, HashSet::len)
+    }
+
+    /// How many questions `guild` has been asked since its bank was last reset.
+    pub fn asked(&self, guild: GuildId) -> usize {
+        self.used.get(&guild).map_or(0, HashSet::len)
+    }
+
+    /// The difficulty of each question `guild` hasn't been asked yet.
+    pub fn remaining_difficulties(&self, guild: GuildId) -> Vec<u8> {
+        let used = self.used.get(&guild);
+        self.questions
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !used.map_or(false, |used| used.contains(index)))
+            .map(|(_, question)| question.difficulty)
+            .collect()
+    }
+
+    /// Every category in the bank, along with how many of its questions `guild` hasn't been asked yet.
+    pub fn categories(&self, guild: GuildId) -> BTreeMap<&str, usize> {
+        let used = self.used.get(&guild);
+        let mut categories = BTreeMap::new();
+        for (index, question) in self.questions.iter().enumerate() {
+            let unused = categories.entry(question.category.as_str()).or_insert(0);
+            if !used.map_or(false, |used| used.contains(&index)) {
+                *unused += 1;
+            }
+        }
+        categories
+    }
+
+    /// Picks a random question `guild` hasn't been asked yet and marks it as asked, only picking from `category`
+    /// (ignoring case) if one is given. Otherwise any category but those in `exclude` can be picked.
+    pub fn draw(&mut self, guild: GuildId, category: Option<&str>, exclude: &[&str]) -> Option<(usize, &Question)> {
+        let questions = &self.questions;
+        let used = self.used.entry(guild).or_default();
+        let allowed = |question: &Question| match category {
+            Some(category) => question.category.eq_ignore_ascii_case(category.trim()),
+            None => !exclude.iter().any(|excluded| question.category.eq_ignore_ascii_case(excluded.trim())),
+        };
+        let unused: Vec<usize> = (0..questions.len())
+            .filter(|index| !used.contains(index))
+            .filter(|index| allowed(&questions[*index]))
+            .collect();
+        if unused.is_empty() {
+            return None;
+        }
+
+        let index = unused[random_below(unused.len())];
+        used.insert(index);
+        Some((index, &self.questions[index]))
+    }
+
+    /// Makes every question available to `guild` again.
+    pub fn reset(&mut self, guild: GuildId) {
+        self.used.remove(&guild);
+    }
+}
+
+/// Drops every question whose prompt, ignoring case and extra whitespace, was already seen earlier in
+/// `questions`. The first of each keeps its answer, category and difficulty.
+pub fn dedupe_questions(questions: Vec<Question>) -> Vec<Question> {
+    let mut seen = HashSet::new();
+    questions
+        .into_iter()
+        .filter(|question| {
+            let mut hasher = DefaultHasher::new();
+            normalize(&question.prompt).hash(&mut hasher);
+            seen.insert(hasher.finish())
+        })
+        .collect()
+}
+
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Whether `submitted` is close enough to `answer` to count, ignoring case and extra whitespace and, for
+/// answers longer than 5 characters, forgiving up to two typos.
+pub fn answer_matches(submitted: &str, answer: &str) -> bool {
+    let (submitted, answer) = (normalize(submitted), normalize(answer));
+    if submitted == answer {
+        return true;
+    }
+
+    answer.chars().count() > 5 && levenshtein(&submitted, &answer) <= 2
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + if a_char == *b_char { 0 } else { 1 };
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+// Picking questions doesn't need a good RNG, the randomly seeded std hasher is plenty without pulling in a crate.
+fn random_below(bound: usize) -> usize {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_usize(bound);
+    (hasher.finish() % bound as u64) as usize
+}
+
+const OPENTDB_URL: &str = "https://opentdb.com/api.php";
+
+#[derive(Debug)]
+pub enum FetchError {
+    Http(reqwest::Error),
+    // Open Trivia DB allows one request every 5 seconds per IP.
+    RateLimited,
+    // The response code for anything else that went wrong, like an unknown category.
+    Api(u8),
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::Http(err) => write!(f, "{}", err),
+            FetchError::RateLimited => f.write_str("rate limited, try again in a few seconds"),
+            FetchError::Api(2) => f.write_str("the amount or category isn't valid"),
+            FetchError::Api(code) => write!(f, "Open Trivia DB responded with code {}", code),
+        }
+    }
+}
+
+impl From<reqwest::Error> for FetchError {
+    fn from(err: reqwest::Error) -> FetchError {
+        FetchError::Http(err)
+    }
+}
+
+#[derive(Deserialize)]
+struct OpentdbResponse {
+    response_code: u8,
+    #[serde(default)]
+    results: Vec<OpentdbQuestion>,
+}
+
+#[derive(Deserialize)]
+struct OpentdbQuestion {
+    category: String,
+    difficulty: String,
+    question: String,
+    correct_answer: String,
+}
+
+/// The name Open Trivia DB gives one of its numbered categories.
+pub fn opentdb_category_name(category: u32) -> Option<&'static str> {
+    let name = match category {
+        9 => "General Knowledge",
+        10 => "Entertainment: Books",
+        11 => "Entertainment: Film",
+        12 => "Entertainment: Music",
+        13 => "Entertainment: Musicals & Theatres",
+        14 => "Entertainment: Television",
+        15 => "Entertainment: Video Games",
+        16 => "Entertainment: Board Games",
+        17 => "Science & Nature",
+        18 => "Science: Computers",
+        19 => "Science: Mathematics",
+        20 => "Mythology",
+        21 => "Sports",
+        22 => "Geography",
+        23 => "History",
+        24 => "Politics",
+        25 => "Art",
+        26 => "Celebrities",
+        27 => "Animals",
+        28 => "Vehicles",
+        29 => "Entertainment: Comics",
+        30 => "Science: Gadgets",
+        31 => "Entertainment: Japanese Anime & Manga",
+        32 => "Entertainment: Cartoon & Animations",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Fetches `amount` (at most 50) random questions from Open Trivia DB, optionally from one of its numbered
+/// categories. Running out of questions in a category isn't an error, there are just fewer questions.
+pub async fn fetch_opentdb(amount: u8, category: Option<u32>) -> Result<Vec<Question>, FetchError> {
+    let mut url = format!("{}?amount={}", OPENTDB_URL, amount);
+    if let Some(category) = category {
+        url.push_str(&format!("&category={}", category));
+    }
+
+    let response: OpentdbResponse = reqwest::get(&url).await?.error_for_status()?.json().await?;
+    match response.response_code {
+        // 1 is not enough questions for the amount asked for.
+        0 | 1 => {},
+        5 => return Err(FetchError::RateLimited),
+        code => return Err(FetchError::Api(code)),
+    }
+
+    Ok(response.results
+        .into_iter()
+        .map(|question| Question {
+            prompt: unescape_html(&question.question),
+            answers: vec![unescape_html(&question.correct_answer)],
+            // Questions come back with their category's name, the number asked for is only a fallback.
+            category: match unescape_html(&question.category) {
+                name if name.is_empty() => category.and_then(opentdb_category_name).unwrap_or_default().to_string(),
+                name => name,
+            },
+            difficulty: match question.difficulty.as_str() {
+                "easy" => 1,
+                "medium" => 2,
+                _ => 3,
+            },
+        })
+        .collect())
+}
+
+// Open Trivia DB escapes its text as HTML. Numeric entities cover anything, named ones only the ones it tends to use.
+fn unescape_html(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest.find(';').map(|end| (&rest[1..end], end));
+        let decoded = entity.and_then(|(entity, end)| {
+            let c = match entity {
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "nbsp" => Some('\u{a0}'),
+                "shy" => Some('\u{ad}'),
+                "hellip" => Some('…'),
+                "ndash" => Some('–'),
+                "mdash" => Some('—'),
+                "lsquo" => Some('‘'),
+                "rsquo" => Some('’'),
+                "ldquo" => Some('“'),
+                "rdquo" => Some('”'),
+                "eacute" => Some('é'),
+                "aacute" => Some('á'),
+                "iacute" => Some('í'),
+                "oacute" => Some('ó'),
+                "uacute" => Some('ú'),
+                "ntilde" => Some('ñ'),
+                "auml" => Some('ä'),
+                "ouml" => Some('ö'),
+                "uuml" => Some('ü'),
+                "szlig" => Some('ß'),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| entity.strip_prefix('#').map(|decimal| decimal.parse()))
+                    .and_then(Result::ok)
+                    .and_then(std::char::from_u32),
+            };
+            c.map(|c| (c, end))
+        });
+
+        match decoded {
+            Some((c, end)) => {
+                unescaped.push(c);
+                rest = &rest[end + 1..];
+            },
+            // A lone `&`, keep it as it is.
+            None => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            },
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
@@ -0,0 +1,76 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serenity::{
+    builder::EditRole,
+    http::{error::Error as HttpError, Http},
+    model::{guild::Role, id::GuildId},
+    prelude::SerenityError,
+};
+
+// Discord's role edit bucket is small, so keep edits in a guild comfortably apart.
+const EDIT_SPACING: Duration = Duration::from_millis(500);
+const MAX_RETRIES: u32 = 3;
+
+/// Serializes role edits per guild so bursts of recolors/renames don't trip the rate limits.
+///
+/// Each guild gets its own async lock holding the time of its last edit, callers wait their turn and
+/// are spaced out by `EDIT_SPACING`, backing off and retrying when Discord still answers with a 429.
+pub struct RoleEditQueue {
+    guilds: Mutex<HashMap<GuildId, Arc<tokio::sync::Mutex<Option<Instant>>>>>,
+}
+
+impl RoleEditQueue {
+    pub fn new() -> RoleEditQueue {
+        RoleEditQueue {
+            guilds: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn edit<F>(&self, http: &Arc<Http>, role: &Role, f: F) -> Result<Role, SerenityError>
+    where
+        F: Fn(&mut EditRole) -> &mut EditRole,
+    {
+        let slot = {
+            let mut guilds = self.guilds.lock().unwrap();
+            guilds.entry(role.guild_id).or_default().clone()
+        };
+
+        let mut last_edit = slot.lock().await;
+        let mut backoff = EDIT_SPACING;
+        let mut attempt = 0;
+        loop {
+            if let Some(last_edit) = *last_edit {
+                let elapsed = last_edit.elapsed();
+                if elapsed < EDIT_SPACING {
+                    tokio::time::sleep(EDIT_SPACING - elapsed).await;
+                }
+            }
+
+            let result = role.edit(http.clone(), |r| f(r)).await;
+            *last_edit = Some(Instant::now());
+
+            match result {
+                Err(err) if attempt < MAX_RETRIES && is_rate_limited(&err) => {
+                    attempt += 1;
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                },
+                result => return result,
+            }
+        }
+    }
+}
+
+fn is_rate_limited(err: &SerenityError) -> bool {
+    match err {
+        SerenityError::Http(http_err) => match &**http_err {
+            HttpError::UnsuccessfulRequest(response) => response.status_code.as_u16() == 429,
+            _ => false,
+        },
+        _ => false,
+    }
+}
@@ -0,0 +1,21 @@
+use std::{fs, io, path::Path};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Reads the JSON at `path`, or `None` if the file doesn't exist yet.
+pub fn load_json<T: DeserializeOwned>(path: &Path) -> io::Result<Option<T>> {
+    match fs::read_to_string(path) {
+        Ok(contents) => serde_json::from_str(&contents).map(Some).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Writes `value` to `path` as JSON. It's written next to the real file first and then moved over it, so a crash
+/// mid-write can't leave a truncated file behind.
+pub fn save_json_atomic<T: Serialize + ?Sized>(path: &Path, value: &T) -> io::Result<()> {
+    let json = serde_json::to_string_pretty(value).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let temp = path.with_extension("tmp");
+    fs::write(&temp, json)?;
+    fs::rename(&temp, path)
+}
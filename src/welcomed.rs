@@ -0,0 +1,39 @@
+use std::{
+    collections::HashSet,
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use serenity::model::id::GuildId;
+
+use crate::storage::{load_json, save_json_atomic};
+
+/// Guilds that have already been sent the welcome message, kept on disk so leaving and rejoining or a restart
+/// doesn't welcome a guild twice.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Welcomed {
+    #[serde(skip)]
+    path: PathBuf,
+    guilds: HashSet<u64>,
+}
+
+impl Welcomed {
+    /// Reads the welcomed guilds at `path`, starting without any if the file doesn't exist yet.
+    pub fn load(path: &Path) -> io::Result<Welcomed> {
+        let mut welcomed: Welcomed = load_json(path)?.unwrap_or_default();
+        welcomed.path = path.to_path_buf();
+        Ok(welcomed)
+    }
+
+    /// Marks `guild` as welcomed, returning whether it wasn't already. An error is only returned for a guild that
+    /// wasn't welcomed before, which then won't be remembered past a restart.
+    pub fn insert(&mut self, guild: GuildId) -> io::Result<bool> {
+        if !self.guilds.insert(guild.0) {
+            return Ok(false)
+        }
+
+        save_json_atomic(&self.path, self)?;
+        Ok(true)
+    }
+}